@@ -1,21 +1,46 @@
-use crate::client::Client;
-use crate::model::{Category, Sort, Torrent};
+use crate::config::Config;
+use crate::filter::PageFilter;
+use crate::group::{self, Preference, RowKind};
+use crate::sink::Sink;
 use crate::theme::Theme;
-use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
-use ratatui::widgets::TableState;
+use crate::tui::DateFormat;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind};
+use nyaa_rs::client::Client;
+use nyaa_rs::error::Error;
+use nyaa_rs::model::{Category, FileEntry, Filter, Order, Sort, Torrent, TorrentDetails};
+use nyaa_rs::release::Release;
+use nyaa_rs::site::Site;
+use ratatui::layout::{Position, Rect};
+use ratatui::widgets::{ListState, TableState};
+use reqwest::StatusCode;
+use std::collections::HashSet;
 use std::path::PathBuf;
-use std::time::SystemTime;
+use std::time::{Instant, SystemTime};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InputMode {
     Normal,
     Editing,
+    CategoryPicker,
+    SinkPicker,
+    Copy,
+    /// waiting for the column key after `o`
+    LocalSort,
+    /// typing the `/` filter
+    Filter,
+    Details,
+    /// the `?` key list
+    Help,
 }
 
 pub struct SearchState {
     pub input: String,
     pub mode: InputMode,
     pub is_loading: bool,
+    pub loading_since: Option<Instant>,
     pub messages: Vec<String>,
 }
 
@@ -25,32 +50,267 @@ impl Default for SearchState {
             input: String::new(),
             mode: InputMode::Editing,
             is_loading: false,
+            loading_since: None,
             messages: Vec::new(),
         }
     }
 }
 
+/// a column the loaded page can be re-ordered by without asking the site
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalSort {
+    Seeders,
+    Leechers,
+    /// seeders per leecher
+    Ratio,
+    Size,
+    Date,
+    Title,
+    Downloads,
+}
+
+impl LocalSort {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Seeders => "seeders",
+            Self::Leechers => "leechers",
+            Self::Ratio => "ratio",
+            Self::Size => "size",
+            Self::Date => "date",
+            Self::Title => "title",
+            Self::Downloads => "downloads",
+        }
+    }
+
+    /// titles read best a to z, everything else biggest or newest first
+    fn default_order(self) -> Order {
+        match self {
+            Self::Title => Order::Ascending,
+            _ => Order::Descending,
+        }
+    }
+
+    fn compare(self, a: &Torrent, b: &Torrent) -> std::cmp::Ordering {
+        match self {
+            Self::Seeders => a.seeders.cmp(&b.seeders),
+            Self::Leechers => a.leechers.cmp(&b.leechers),
+            // cross multiplied so there is no float and no division by zero
+            Self::Ratio => (u64::from(a.seeders) * u64::from(b.leechers.max(1)))
+                .cmp(&(u64::from(b.seeders) * u64::from(a.leechers.max(1)))),
+            Self::Size => a.size_bytes.cmp(&b.size_bytes),
+            Self::Date => a.date.cmp(&b.date),
+            Self::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+            Self::Downloads => a.downloads.cmp(&b.downloads),
+        }
+    }
+}
+
 pub struct TableData {
     pub results: Vec<Torrent>,
+    /// the parsed title of each row of `results`
+    pub releases: Vec<Release>,
+    /// `results` in the order the site returned them
+    server_results: Vec<(Torrent, Release)>,
+    /// re-orders `results` on top of the site's sort
+    pub local_sort: Option<(LocalSort, Order)>,
+    /// header cells as last drawn, for clicking on them
+    pub column_areas: Vec<(Rect, LocalSort)>,
+    /// narrows `results` without refetching
+    pub page_filter: PageFilter,
+    /// highlighted title chars, one entry per row of `results`
+    pub matches: Vec<Vec<usize>>,
+    /// collapse releases of the same series and episode
+    pub grouped: bool,
+    /// how the best release of a group is picked
+    pub preference: Preference,
+    /// keys of the groups that show all their releases
+    expanded: HashSet<String>,
+    /// where each row of `results` sits in the grouped view
+    pub kinds: Vec<RowKind>,
     pub state: TableState,
     pub current_page: u32,
     pub current_sort: Sort,
+    pub current_order: Order,
+    /// index into `App::sites`
+    pub current_site: usize,
+    pub current_category: Category,
+    pub current_filter: Filter,
     pub last_selected_index: Option<usize>,
+    /// marked torrents in the order they were marked, kept across pages
+    pub marked: Vec<Torrent>,
 }
 
 impl Default for TableData {
     fn default() -> Self {
         Self {
             results: Vec::new(),
+            releases: Vec::new(),
+            server_results: Vec::new(),
+            local_sort: None,
+            column_areas: Vec::new(),
+            page_filter: PageFilter::default(),
+            matches: Vec::new(),
+            grouped: false,
+            preference: Preference::default(),
+            expanded: HashSet::new(),
+            kinds: Vec::new(),
             state: TableState::default(),
             current_page: 1,
             current_sort: Sort::Seeders,
+            current_order: Order::default(),
+            current_site: 0,
+            current_category: Category::All,
+            current_filter: Filter::default(),
             last_selected_index: None,
+            marked: Vec::new(),
         }
     }
 }
 
 impl TableData {
+    pub fn selected(&self) -> Option<&Torrent> {
+        self.state.selected().and_then(|i| self.results.get(i))
+    }
+
+    /// replaces the page and selects its first row
+    pub fn load(&mut self, torrents: Vec<Torrent>) {
+        self.server_results = torrents
+            .into_iter()
+            .map(|t| {
+                let release = t.release();
+                (t, release)
+            })
+            .collect();
+        self.expanded.clear();
+        self.state.select(None);
+        self.refresh();
+    }
+
+    /// off, then the column's natural direction, then the other one, then off
+    pub fn toggle_local_sort(&mut self, column: LocalSort) {
+        self.local_sort = match self.local_sort {
+            Some((current, order)) if current == column => {
+                (order == column.default_order()).then(|| (column, order.flip()))
+            }
+            _ => Some((column, column.default_order())),
+        };
+        self.refresh();
+    }
+
+    pub fn clear_local_sort(&mut self) {
+        self.local_sort = None;
+        self.refresh();
+    }
+
+    /// filters and re-orders the loaded page, keeping the selected torrent
+    /// selected while it is still shown
+    pub fn refresh(&mut self) {
+        let selected = self.selected().map(|t| t.link.clone());
+
+        let mut rows: Vec<(&Torrent, &Release, Vec<usize>)> = self
+            .server_results
+            .iter()
+            .filter_map(|(t, r)| Some((t, r, self.page_filter.matches(&t.title, r)?)))
+            .collect();
+        if let Some((column, order)) = self.local_sort {
+            rows.sort_by(|(a, _, _), (b, _, _)| match order {
+                Order::Ascending => column.compare(a, b),
+                Order::Descending => column.compare(b, a),
+            });
+        }
+        let rows = if self.grouped {
+            group::group(rows, &self.preference, &self.expanded)
+        } else {
+            rows.into_iter().map(|row| (row, RowKind::Single)).collect()
+        };
+        self.results = rows.iter().map(|((t, _, _), _)| (*t).clone()).collect();
+        self.releases = rows.iter().map(|((_, r, _), _)| (*r).clone()).collect();
+        (self.matches, self.kinds) = rows.into_iter().map(|((_, _, m), k)| (m, k)).unzip();
+
+        let index = selected
+            .and_then(|link| self.results.iter().position(|t| t.link == link))
+            .or((!self.results.is_empty()).then_some(0));
+        self.state.select(index);
+        self.last_selected_index = index;
+    }
+
+    pub fn toggle_grouped(&mut self) {
+        self.grouped = !self.grouped;
+        self.refresh();
+    }
+
+    /// opens or closes the selected row's group, a release inside an open
+    /// group closes it and selects the group's best
+    pub fn toggle_expanded(&mut self) {
+        let Some(i) = self.state.selected() else {
+            return;
+        };
+        match self.kinds.get(i) {
+            Some(RowKind::Group { key, .. }) => {
+                if !self.expanded.remove(key) {
+                    self.expanded.insert(key.clone());
+                }
+            }
+            Some(RowKind::Member { key }) => {
+                let key = key.clone();
+                let header = self.kinds[..i]
+                    .iter()
+                    .rposition(|k| matches!(k, RowKind::Group { key: k, .. } if *k == key));
+                self.state.select(header);
+                self.expanded.remove(&key);
+            }
+            _ => return,
+        }
+        self.refresh();
+    }
+
+    pub fn is_marked(&self, torrent: &Torrent) -> bool {
+        self.marked.iter().any(|t| t.link == torrent.link)
+    }
+
+    pub fn toggle_mark(&mut self) {
+        let Some(torrent) = self.selected().cloned() else {
+            return;
+        };
+        self.toggle(torrent);
+    }
+
+    pub fn mark_all(&mut self) {
+        for torrent in &self.results {
+            if !self.marked.iter().any(|t| t.link == torrent.link) {
+                self.marked.push(torrent.clone());
+            }
+        }
+    }
+
+    pub fn invert_marks(&mut self) {
+        for torrent in self.results.clone() {
+            self.toggle(torrent);
+        }
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+    }
+
+    fn toggle(&mut self, torrent: Torrent) {
+        match self.marked.iter().position(|t| t.link == torrent.link) {
+            Some(i) => {
+                self.marked.remove(i);
+            }
+            None => self.marked.push(torrent),
+        }
+    }
+
+    /// what batch actions apply to: every marked torrent, or the selected row
+    pub fn targets(&self) -> Vec<Torrent> {
+        if self.marked.is_empty() {
+            self.selected().cloned().into_iter().collect()
+        } else {
+            self.marked.clone()
+        }
+    }
+
     pub fn next(&mut self) -> bool {
         let i = match self.state.selected() {
             Some(i) => {
@@ -90,30 +350,205 @@ impl TableData {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetailsPane {
+    Files,
+    Comments,
+}
+
+pub struct DetailsState {
+    pub torrent: Torrent,
+    pub details: Option<TorrentDetails>,
+    pub pane: DetailsPane,
+    pub files: ListState,
+    pub collapsed: HashSet<Vec<usize>>,
+    pub scroll: u16,
+    pub comment_scroll: u16,
+}
+
+pub struct VisibleFile<'a> {
+    pub depth: usize,
+    pub path: Vec<usize>,
+    pub entry: &'a FileEntry,
+}
+
+impl DetailsState {
+    fn new(torrent: Torrent, pane: DetailsPane) -> Self {
+        Self {
+            torrent,
+            details: None,
+            pane,
+            files: ListState::default(),
+            collapsed: HashSet::new(),
+            scroll: 0,
+            comment_scroll: 0,
+        }
+    }
+
+    /// flattens the file tree into the rows currently shown, skipping the
+    /// contents of collapsed folders
+    pub fn visible_files(&self) -> Vec<VisibleFile<'_>> {
+        fn walk<'a>(
+            entries: &'a [FileEntry],
+            path: &mut Vec<usize>,
+            collapsed: &HashSet<Vec<usize>>,
+            out: &mut Vec<VisibleFile<'a>>,
+        ) {
+            for (i, entry) in entries.iter().enumerate() {
+                path.push(i);
+                out.push(VisibleFile {
+                    depth: path.len() - 1,
+                    path: path.clone(),
+                    entry,
+                });
+                if !collapsed.contains(path) {
+                    walk(&entry.children, path, collapsed, out);
+                }
+                path.pop();
+            }
+        }
+
+        let mut out = Vec::new();
+        if let Some(details) = &self.details {
+            walk(&details.files, &mut Vec::new(), &self.collapsed, &mut out);
+        }
+        out
+    }
+
+    pub fn toggle_selected(&mut self) {
+        let Some(path) = self.files.selected().and_then(|i| {
+            self.visible_files()
+                .into_iter()
+                .nth(i)
+                .filter(|f| f.entry.is_folder())
+                .map(|f| f.path)
+        }) else {
+            return;
+        };
+        if !self.collapsed.remove(&path) {
+            self.collapsed.insert(path);
+        }
+    }
+}
+
+/// the query parameters that produced the results on screen, so a cancelled
+/// request can put them back
+#[derive(Debug, Clone, Copy)]
+struct RestorePoint {
+    page: u32,
+    sort: Sort,
+    order: Order,
+    site: usize,
+    category: Category,
+    filter: Filter,
+}
+
+impl RestorePoint {
+    fn capture(table: &TableData) -> Self {
+        Self {
+            page: table.current_page,
+            sort: table.current_sort,
+            order: table.current_order,
+            site: table.current_site,
+            category: table.current_category,
+            filter: table.current_filter,
+        }
+    }
+
+    fn apply(self, table: &mut TableData) {
+        table.current_page = self.page;
+        table.current_sort = self.sort;
+        table.current_order = self.order;
+        table.current_site = self.site;
+        table.current_category = self.category;
+        table.current_filter = self.filter;
+    }
+}
+
+pub enum AppEvent {
+    Search {
+        id: u64,
+        result: nyaa_rs::Result<Vec<Torrent>>,
+    },
+    Details {
+        id: u64,
+        result: nyaa_rs::Result<TorrentDetails>,
+    },
+    Download(nyaa_rs::Result<PathBuf>),
+    Sent {
+        sink: String,
+        result: Result<Vec<String>>,
+    },
+    Message(String),
+}
+
 pub struct App {
     pub search: SearchState,
     pub table: TableData,
     pub client: Client,
+    pub config: Config,
+    /// the configured sinks, the first one is what enter uses
+    pub sinks: Vec<Sink>,
+    /// never empty, `table.current_site` picks one
+    pub sites: Vec<Site>,
     pub should_quit: bool,
     pub animation_tick: usize,
     pub theme: Theme,
     pub theme_last_modified: Option<SystemTime>,
     pub theme_path: Option<PathBuf>,
+    pub category_state: ListState,
+    pub sink_state: ListState,
+    pub details: Option<DetailsState>,
+    pub date_format: DateFormat,
+    /// extra columns parsed from the titles
+    pub release_columns: bool,
+    events_tx: UnboundedSender<AppEvent>,
+    events_rx: UnboundedReceiver<AppEvent>,
+    pending_search: Option<JoinHandle<()>>,
+    search_id: u64,
+    restore_point: Option<RestorePoint>,
+    pending_details: Option<JoinHandle<()>>,
+    details_id: u64,
 }
 
 impl Default for App {
     fn default() -> Self {
         let (theme, theme_last_modified) = Theme::load();
         let theme_path = Theme::path();
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let mut search = SearchState::default();
+        let config = Config::load().unwrap_or_else(|e| {
+            search.messages.push(format!("{e:#}, using the defaults"));
+            Config::default()
+        });
         Self {
-            search: SearchState::default(),
-            table: TableData::default(),
-            client: Client::new(),
+            search,
+            table: TableData {
+                grouped: config.grouped,
+                preference: config.preference.clone(),
+                ..TableData::default()
+            },
+            client: config.client(),
+            sinks: load_sinks(&config),
+            sites: config.sites(),
+            date_format: config.date_format,
+            release_columns: config.release_columns,
+            config,
             should_quit: false,
             animation_tick: 0,
             theme,
             theme_last_modified,
             theme_path,
+            category_state: ListState::default(),
+            sink_state: ListState::default(),
+            details: None,
+            events_tx,
+            events_rx,
+            pending_search: None,
+            search_id: 0,
+            restore_point: None,
+            pending_details: None,
+            details_id: 0,
         }
     }
 }
@@ -123,7 +558,25 @@ impl App {
         Self::default()
     }
 
-    pub async fn handle_key_event(&mut self, key: KeyEvent) {
+    /// clicking a column header toggles the local sort on it
+    pub fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        if self.search.mode != InputMode::Normal
+            || mouse.kind != MouseEventKind::Down(MouseButton::Left)
+        {
+            return;
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        if let Some(&(_, column)) = self
+            .table
+            .column_areas
+            .iter()
+            .find(|(area, _)| area.contains(position))
+        {
+            self.table.toggle_local_sort(column);
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) {
         if key.kind != KeyEventKind::Press {
             return;
         }
@@ -131,29 +584,69 @@ impl App {
         match self.search.mode {
             InputMode::Normal => match key.code {
                 KeyCode::Char('q') => self.should_quit = true,
+                KeyCode::Esc if self.search.is_loading => self.cancel_search(),
+                KeyCode::Esc if self.table.page_filter.is_active() => {
+                    self.table.page_filter.clear();
+                    self.table.refresh();
+                }
+                KeyCode::Char('/') => self.search.mode = InputMode::Filter,
+                KeyCode::Char('?') => self.search.mode = InputMode::Help,
                 KeyCode::Tab | KeyCode::Char('i') => {
                     self.search.mode = InputMode::Editing;
                 }
+                #[allow(clippy::collapsible_match)]
                 KeyCode::Char('s') | KeyCode::Down | KeyCode::Char('j') => {
                     if self.table.next() {
                         self.reset_animation();
                     }
                 }
+                #[allow(clippy::collapsible_match)]
                 KeyCode::Char('w') | KeyCode::Up | KeyCode::Char('k') => {
                     if self.table.previous() {
                         self.reset_animation();
                     }
                 }
                 KeyCode::Char('z') => {
-                    self.cycle_sort().await;
+                    self.cycle_sort();
                 }
+                KeyCode::Char('x') => self.toggle_order(),
+                KeyCode::Char(c @ '1'..='7') => {
+                    let i = c as usize - '1' as usize;
+                    if let Some(&sort) = Sort::ALL.get(i) {
+                        self.set_sort(sort);
+                    }
+                }
+                KeyCode::Char('c') => self.open_category_picker(),
+                KeyCode::Char('v') => self.open_details(DetailsPane::Files),
+                KeyCode::Char('m') => self.open_details(DetailsPane::Comments),
+                KeyCode::Char('f') => self.cycle_filter(),
+                KeyCode::Char('S') => self.cycle_site(),
+                KeyCode::Char('D') => self.date_format = self.date_format.next(),
+                KeyCode::Char('R') => self.release_columns = !self.release_columns,
+                KeyCode::Char('G') => self.table.toggle_grouped(),
+                KeyCode::Char('e') => self.table.toggle_expanded(),
                 KeyCode::Right | KeyCode::Char('d') => {
-                    self.next_page().await;
+                    self.next_page();
                 }
                 KeyCode::Left | KeyCode::Char('a') => {
-                    self.prev_page().await;
+                    self.prev_page();
+                }
+                KeyCode::Enter => self.send_to_sink(0),
+                KeyCode::Char(' ') => {
+                    self.table.toggle_mark();
+                    if self.table.next() {
+                        self.reset_animation();
+                    }
+                }
+                KeyCode::Char('A') => self.table.mark_all(),
+                KeyCode::Char('I') => self.table.invert_marks(),
+                KeyCode::Char('u') => self.table.clear_marks(),
+                KeyCode::Char('y') if !self.table.targets().is_empty() => {
+                    self.search.mode = InputMode::Copy;
                 }
-                KeyCode::Enter => self.open_magnet(),
+                KeyCode::Char('o') => self.search.mode = InputMode::LocalSort,
+                KeyCode::Char('p') => self.open_sink_picker(),
+                KeyCode::Char('t') => self.download_torrent(),
                 _ => {}
             },
             InputMode::Editing => match key.code {
@@ -162,7 +655,7 @@ impl App {
                 }
                 KeyCode::Enter => {
                     self.search.mode = InputMode::Normal;
-                    self.perform_search().await;
+                    self.perform_search();
                 }
                 KeyCode::Char(c) => {
                     self.search.input.push(c);
@@ -172,6 +665,143 @@ impl App {
                 }
                 _ => {}
             },
+            InputMode::Filter => match key.code {
+                KeyCode::Enter => self.search.mode = InputMode::Normal,
+                KeyCode::Esc => {
+                    self.search.mode = InputMode::Normal;
+                    self.table.page_filter.clear();
+                    self.table.refresh();
+                }
+                #[allow(clippy::collapsible_match)]
+                KeyCode::Down => {
+                    if self.table.next() {
+                        self.reset_animation();
+                    }
+                }
+                #[allow(clippy::collapsible_match)]
+                KeyCode::Up => {
+                    if self.table.previous() {
+                        self.reset_animation();
+                    }
+                }
+                KeyCode::Tab => {
+                    self.table.page_filter.toggle_regex();
+                    self.table.refresh();
+                }
+                KeyCode::Backspace => {
+                    self.table.page_filter.pop();
+                    self.table.refresh();
+                }
+                KeyCode::Char(c) => {
+                    self.table.page_filter.push(c);
+                    self.table.refresh();
+                }
+                _ => {}
+            },
+            InputMode::LocalSort => {
+                self.search.mode = InputMode::Normal;
+                let column = match key.code {
+                    KeyCode::Char('s') => Some(LocalSort::Seeders),
+                    KeyCode::Char('l') => Some(LocalSort::Leechers),
+                    KeyCode::Char('r') => Some(LocalSort::Ratio),
+                    KeyCode::Char('b') => Some(LocalSort::Size),
+                    KeyCode::Char('d') => Some(LocalSort::Date),
+                    KeyCode::Char('t') => Some(LocalSort::Title),
+                    KeyCode::Char('c') => Some(LocalSort::Downloads),
+                    KeyCode::Char('x') => None,
+                    _ => return,
+                };
+                match column {
+                    Some(column) => self.table.toggle_local_sort(column),
+                    None => self.table.clear_local_sort(),
+                }
+            }
+            InputMode::Copy => {
+                self.search.mode = InputMode::Normal;
+                match key.code {
+                    KeyCode::Char('m') => self.copy("magnet", |t| Some(t.magnet_url.clone())),
+                    KeyCode::Char('l') => self.copy("link", |t| Some(t.link.clone())),
+                    KeyCode::Char('h') => self.copy("info hash", Torrent::info_hash),
+                    KeyCode::Char('t') => self.copy("title", |t| Some(t.title.clone())),
+                    _ => {}
+                }
+            }
+            InputMode::Help => {
+                if matches!(
+                    key.code,
+                    KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q')
+                ) {
+                    self.search.mode = InputMode::Normal;
+                }
+            }
+            InputMode::SinkPicker => match key.code {
+                KeyCode::Esc | KeyCode::Char('p') | KeyCode::Char('q') => {
+                    self.search.mode = InputMode::Normal;
+                }
+                KeyCode::Char('s') | KeyCode::Down | KeyCode::Char('j') => {
+                    self.sink_state.select_next();
+                }
+                KeyCode::Char('w') | KeyCode::Up | KeyCode::Char('k') => {
+                    self.sink_state.select_previous();
+                }
+                KeyCode::Enter => {
+                    self.search.mode = InputMode::Normal;
+                    if let Some(i) = self.sink_state.selected() {
+                        self.send_to_sink(i);
+                    }
+                }
+                _ => {}
+            },
+            InputMode::CategoryPicker => match key.code {
+                KeyCode::Esc | KeyCode::Char('c') | KeyCode::Char('q') => {
+                    self.search.mode = InputMode::Normal;
+                }
+                KeyCode::Char('s') | KeyCode::Down | KeyCode::Char('j') => {
+                    self.category_state.select_next();
+                }
+                KeyCode::Char('w') | KeyCode::Up | KeyCode::Char('k') => {
+                    self.category_state.select_previous();
+                }
+                KeyCode::Enter => self.select_category(),
+                _ => {}
+            },
+            InputMode::Details => {
+                let Some(details) = &mut self.details else {
+                    self.search.mode = InputMode::Normal;
+                    return;
+                };
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('v') | KeyCode::Char('q') => self.close_details(),
+                    KeyCode::Tab | KeyCode::Char('m') => {
+                        details.pane = match details.pane {
+                            DetailsPane::Files => DetailsPane::Comments,
+                            DetailsPane::Comments => DetailsPane::Files,
+                        };
+                    }
+                    KeyCode::Char('s') | KeyCode::Down | KeyCode::Char('j') => match details.pane {
+                        DetailsPane::Files => details.files.select_next(),
+                        DetailsPane::Comments => {
+                            details.comment_scroll = details.comment_scroll.saturating_add(1);
+                        }
+                    },
+                    KeyCode::Char('w') | KeyCode::Up | KeyCode::Char('k') => match details.pane {
+                        DetailsPane::Files => details.files.select_previous(),
+                        DetailsPane::Comments => {
+                            details.comment_scroll = details.comment_scroll.saturating_sub(1);
+                        }
+                    },
+                    KeyCode::Enter | KeyCode::Char(' ') if details.pane == DetailsPane::Files => {
+                        details.toggle_selected();
+                    }
+                    KeyCode::PageDown | KeyCode::Char('J') => {
+                        details.scroll = details.scroll.saturating_add(3);
+                    }
+                    KeyCode::PageUp | KeyCode::Char('K') => {
+                        details.scroll = details.scroll.saturating_sub(3);
+                    }
+                    _ => {}
+                }
+            }
         }
     }
 
@@ -200,69 +830,496 @@ impl App {
         self.animation_tick = 0;
     }
 
-    pub async fn perform_search(&mut self) {
+    pub fn poll_events(&mut self) {
+        while let Ok(event) = self.events_rx.try_recv() {
+            match event {
+                AppEvent::Search { id, result } => self.finish_search(id, result),
+                AppEvent::Details { id, result } => self.finish_details(id, result),
+                AppEvent::Download(result) => match result {
+                    Ok(path) => self
+                        .search
+                        .messages
+                        .push(format!("saved {}", path.display())),
+                    Err(e) => self.search.messages.push(error_message(&e)),
+                },
+                AppEvent::Sent { sink, result } => match result {
+                    Ok(output) if output.is_empty() => {
+                        self.search.messages.push(format!("sent to {sink}"));
+                    }
+                    // the footer shows one message, so the output goes in as one
+                    Ok(output) => self
+                        .search
+                        .messages
+                        .push(format!("{sink}: {}", output.join(" | "))),
+                    Err(e) => self.search.messages.push(format!("{sink}: {e:#}")),
+                },
+                AppEvent::Message(message) => self.search.messages.push(message),
+            }
+        }
+    }
+
+    pub fn perform_search(&mut self) {
         if self.search.input.trim().is_empty() {
             return;
         }
 
+        // a newer search always wins, so drop whatever is still in flight
+        if let Some(handle) = self.pending_search.take() {
+            handle.abort();
+        }
+        self.search_id = self.search_id.wrapping_add(1);
+
         self.search.is_loading = true;
+        self.search.loading_since = Some(Instant::now());
         self.search.messages.clear();
 
+        let id = self.search_id;
+        let client = self.client.clone().with_site(self.site().clone());
+        let tx = self.events_tx.clone();
         let query = self.search.input.clone();
         let sort = self.table.current_sort;
+        let order = self.table.current_order;
         let page = self.table.current_page;
+        let category = self.table.current_category;
+        let filter = self.table.current_filter;
+
+        self.pending_search = Some(tokio::spawn(async move {
+            let result = client
+                .search(&query, category, filter, sort, order, page)
+                .await;
+            let _ = tx.send(AppEvent::Search { id, result });
+        }));
+    }
+
+    pub fn cancel_search(&mut self) {
+        if let Some(handle) = self.pending_search.take() {
+            handle.abort();
+        }
+        // bump the id so a result that was already queued gets ignored
+        self.search_id = self.search_id.wrapping_add(1);
 
-        match self.client.search(&query, Category::All, sort, page).await {
+        if let Some(point) = self.restore_point.take() {
+            point.apply(&mut self.table);
+        }
+        self.stop_loading();
+        self.search.messages.push("search cancelled".to_string());
+    }
+
+    fn stop_loading(&mut self) {
+        self.search.is_loading = false;
+        self.search.loading_since = None;
+    }
+
+    fn save_restore_point(&mut self) {
+        if self.restore_point.is_none() {
+            self.restore_point = Some(RestorePoint::capture(&self.table));
+        }
+    }
+
+    fn finish_search(&mut self, id: u64, result: nyaa_rs::Result<Vec<Torrent>>) {
+        if id != self.search_id {
+            return;
+        }
+        self.pending_search = None;
+        let restore_point = self.restore_point.take();
+
+        match result {
             Ok(torrents) => {
-                self.table.results = torrents;
-                self.table.state.select(Some(0));
-                self.table.last_selected_index = Some(0);
+                self.table.load(torrents);
                 self.reset_animation();
             }
             Err(e) => {
-                self.search.messages.push(format!("error: {}", e));
+                // keep the header in line with the results that are still shown
+                let kept = restore_point.is_some();
+                if let Some(point) = restore_point {
+                    point.apply(&mut self.table);
+                }
+                let message = match e {
+                    Error::EmptyPage if kept => "no results, kept the previous ones".to_string(),
+                    Error::EmptyPage => {
+                        self.table.load(Vec::new());
+                        format!("no results for \"{}\"", self.search.input.trim())
+                    }
+                    e => error_message(&e),
+                };
+                self.search.messages.push(message);
             }
         }
-        self.search.is_loading = false;
+        self.stop_loading();
     }
 
-    pub async fn next_page(&mut self) {
+    pub fn next_page(&mut self) {
         if self.table.results.is_empty() {
             return;
         }
+        self.save_restore_point();
         self.table.current_page += 1;
-        self.perform_search().await;
+        self.perform_search();
     }
 
-    pub async fn prev_page(&mut self) {
+    pub fn prev_page(&mut self) {
         if self.table.current_page > 1 {
+            self.save_restore_point();
             self.table.current_page -= 1;
-            self.perform_search().await;
+            self.perform_search();
+        }
+    }
+
+    pub fn cycle_sort(&mut self) {
+        self.set_sort(self.table.current_sort.next());
+    }
+
+    pub fn set_sort(&mut self, sort: Sort) {
+        if !self.search.input.trim().is_empty() {
+            self.save_restore_point();
         }
+        self.table.current_sort = sort;
+        self.table.local_sort = None;
+        self.table.current_page = 1;
+        self.perform_search();
     }
 
-    pub async fn cycle_sort(&mut self) {
-        self.table.current_sort = match self.table.current_sort {
-            Sort::Date => Sort::Downloads,
-            Sort::Downloads => Sort::Seeders,
-            Sort::Seeders => Sort::Size,
-            Sort::Size => Sort::Date,
+    pub fn toggle_order(&mut self) {
+        if !self.search.input.trim().is_empty() {
+            self.save_restore_point();
+        }
+        self.table.current_order = self.table.current_order.flip();
+        self.table.local_sort = None;
+        self.table.current_page = 1;
+        self.perform_search();
+    }
+
+    pub fn cycle_filter(&mut self) {
+        if !self.search.input.trim().is_empty() {
+            self.save_restore_point();
+        }
+        self.table.current_filter = match self.table.current_filter {
+            Filter::NoFilter => Filter::NoRemakes,
+            Filter::NoRemakes => Filter::TrustedOnly,
+            Filter::TrustedOnly => Filter::NoFilter,
         };
         self.table.current_page = 1;
+        self.perform_search();
+    }
+
+    pub fn site(&self) -> &Site {
+        &self.sites[self.table.current_site]
+    }
+
+    /// moves to the next configured site, keeping the category if it has one
+    pub fn cycle_site(&mut self) {
+        if self.sites.len() < 2 {
+            return;
+        }
         if !self.search.input.trim().is_empty() {
-            self.perform_search().await;
+            self.save_restore_point();
+        }
+        self.table.current_site = (self.table.current_site + 1) % self.sites.len();
+        if !self
+            .site()
+            .categories()
+            .contains(&self.table.current_category)
+        {
+            self.table.current_category = Category::All;
         }
+        self.table.current_page = 1;
+        self.perform_search();
     }
 
-    pub fn open_magnet(&self) {
-        if let Some(torrent) = self
-            .table
-            .state
+    pub fn open_category_picker(&mut self) {
+        let current = self
+            .site()
+            .categories()
+            .iter()
+            .position(|&c| c == self.table.current_category);
+        self.category_state.select(current);
+        self.search.mode = InputMode::CategoryPicker;
+    }
+
+    pub fn select_category(&mut self) {
+        self.search.mode = InputMode::Normal;
+        let Some(&category) = self
+            .category_state
             .selected()
-            .and_then(|i| self.table.results.get(i))
-            .filter(|t| !t.magnet_url.is_empty())
-        {
-            let _ = open::that(&torrent.magnet_url);
+            .and_then(|i| self.site().categories().get(i))
+        else {
+            return;
+        };
+        if category == self.table.current_category {
+            return;
         }
+
+        if !self.search.input.trim().is_empty() {
+            self.save_restore_point();
+        }
+        self.table.current_category = category;
+        self.table.current_page = 1;
+        self.perform_search();
+    }
+
+    pub fn open_details(&mut self, pane: DetailsPane) {
+        let Some(torrent) = self.table.selected().cloned() else {
+            return;
+        };
+
+        if let Some(handle) = self.pending_details.take() {
+            handle.abort();
+        }
+        self.details_id = self.details_id.wrapping_add(1);
+
+        let id = self.details_id;
+        let client = self.client.clone();
+        let tx = self.events_tx.clone();
+        let target = torrent.clone();
+
+        self.pending_details = Some(tokio::spawn(async move {
+            let result = client.details(&target).await;
+            let _ = tx.send(AppEvent::Details { id, result });
+        }));
+
+        self.details = Some(DetailsState::new(torrent, pane));
+        self.search.mode = InputMode::Details;
+    }
+
+    pub fn close_details(&mut self) {
+        if let Some(handle) = self.pending_details.take() {
+            handle.abort();
+        }
+        self.details = None;
+        self.search.mode = InputMode::Normal;
+    }
+
+    fn finish_details(&mut self, id: u64, result: nyaa_rs::Result<TorrentDetails>) {
+        if id != self.details_id {
+            return;
+        }
+        self.pending_details = None;
+
+        match result {
+            Ok(loaded) => {
+                if let Some(details) = &mut self.details {
+                    details.details = Some(loaded);
+                    details.files.select(Some(0));
+                }
+            }
+            Err(e) => {
+                self.close_details();
+                self.search.messages.push(error_message(&e));
+            }
+        }
+    }
+
+    pub fn download_torrent(&mut self) {
+        let torrents = self.table.targets();
+        if torrents.is_empty() {
+            return;
+        }
+
+        let client = self.client.clone();
+        let tx = self.events_tx.clone();
+        let dir = self.config.download_dir();
+
+        self.search
+            .messages
+            .push(format!("downloading {} torrent(s)", torrents.len()));
+        tokio::spawn(async move {
+            let mut saved = 0;
+            for torrent in &torrents {
+                let result = client.download_torrent(torrent, &dir).await;
+                saved += usize::from(result.is_ok());
+                let _ = tx.send(AppEvent::Download(result));
+            }
+            if torrents.len() > 1 {
+                let _ = tx.send(AppEvent::Message(format!(
+                    "saved {saved}/{} torrents to {}",
+                    torrents.len(),
+                    dir.display()
+                )));
+            }
+        });
+    }
+
+    /// copies one field of every target torrent, one per line
+    pub fn copy(&mut self, what: &str, field: impl Fn(&Torrent) -> Option<String>) {
+        let values: Vec<String> = self
+            .table
+            .targets()
+            .iter()
+            .filter_map(field)
+            .filter(|v| !v.is_empty())
+            .collect();
+        if values.is_empty() {
+            self.search.messages.push(format!("no {what} to copy"));
+            return;
+        }
+
+        let message =
+            match crate::clipboard::copy(&values.join("\n"), self.config.clipboard_fallback) {
+                Ok(()) if values.len() == 1 => format!("copied {what}"),
+                Ok(()) => format!("copied {what} of {} torrents", values.len()),
+                Err(e) => format!("copy failed: {e}"),
+            };
+        self.search.messages.push(message);
+    }
+
+    pub fn open_sink_picker(&mut self) {
+        if self.table.targets().is_empty() {
+            return;
+        }
+        self.sink_state.select(Some(0));
+        self.search.mode = InputMode::SinkPicker;
+    }
+
+    pub fn send_to_sink(&mut self, index: usize) {
+        let torrents = self.table.targets();
+        if torrents.is_empty() {
+            return;
+        }
+        let Some(sink) = self.sinks.get(index).cloned() else {
+            return;
+        };
+
+        let tx = self.events_tx.clone();
+
+        tokio::spawn(async move {
+            let mut sent = 0;
+            for torrent in &torrents {
+                let result = sink.send(torrent).await;
+                sent += usize::from(result.is_ok());
+                let _ = tx.send(AppEvent::Sent {
+                    sink: sink.name().to_string(),
+                    result,
+                });
+            }
+            if torrents.len() > 1 {
+                let _ = tx.send(AppEvent::Message(format!(
+                    "sent {sent}/{} torrents to {}",
+                    torrents.len(),
+                    sink.name()
+                )));
+            }
+        });
+    }
+}
+
+/// what went wrong and what can be done about it
+fn error_message(error: &Error) -> String {
+    let hint = match error {
+        Error::Status { status, .. } if *status == StatusCode::TOO_MANY_REQUESTS => {
+            "wait a minute before searching again"
+        }
+        Error::Status { status, .. } if status.is_server_error() => {
+            "try again later or switch site with S"
+        }
+        Error::Status { status, .. } if *status == StatusCode::NOT_FOUND => {
+            "check the site urls in config.json"
+        }
+        Error::Timeout { .. } => "try again or add a mirror to config.json",
+        Error::Challenge { .. } => "try \"backend\": \"rss\" or another mirror",
+        Error::Parse { .. } => "the site layout may have changed, try \"backend\": \"rss\"",
+        Error::Malformed(_) => "check the site urls in config.json",
+        _ => return format!("error: {error}"),
+    };
+    format!("error: {error}, {hint}")
+}
+
+fn load_sinks(config: &Config) -> Vec<Sink> {
+    let mut sinks: Vec<Sink> = std::iter::once(&config.sink)
+        .chain(&config.sinks)
+        .map(Sink::from_config)
+        .collect();
+    // the system magnet handler is always there as a last resort
+    if !sinks.iter().any(|s| matches!(s, Sink::Open)) {
+        sinks.push(Sink::Open);
+    }
+    sinks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::test_torrent;
+
+    fn torrent(id: u32, seeders: u32, leechers: u32) -> Torrent {
+        Torrent {
+            title: format!("[A] Show - {id:02} (1080p).mkv"),
+            link: format!("https://nyaa.si/view/{id}"),
+            seeders,
+            leechers,
+            ..test_torrent()
+        }
+    }
+
+    fn links(table: &TableData) -> Vec<&str> {
+        table.results.iter().map(|t| t.link.as_str()).collect()
+    }
+
+    #[test]
+    fn local_sort_cycles_natural_flipped_off() {
+        let mut table = TableData::default();
+
+        table.toggle_local_sort(LocalSort::Seeders);
+        assert_eq!(
+            table.local_sort,
+            Some((LocalSort::Seeders, Order::Descending))
+        );
+        table.toggle_local_sort(LocalSort::Seeders);
+        assert_eq!(
+            table.local_sort,
+            Some((LocalSort::Seeders, Order::Ascending))
+        );
+        table.toggle_local_sort(LocalSort::Seeders);
+        assert_eq!(table.local_sort, None);
+
+        table.toggle_local_sort(LocalSort::Title);
+        assert_eq!(table.local_sort, Some((LocalSort::Title, Order::Ascending)));
+        // another column starts over in its own direction
+        table.toggle_local_sort(LocalSort::Size);
+        assert_eq!(table.local_sort, Some((LocalSort::Size, Order::Descending)));
+    }
+
+    #[test]
+    fn ratio_treats_no_leechers_as_one() {
+        use std::cmp::Ordering::*;
+        let cmp = |a: &Torrent, b: &Torrent| LocalSort::Ratio.compare(a, b);
+
+        assert_eq!(cmp(&torrent(1, 10, 0), &torrent(2, 10, 1)), Equal);
+        assert_eq!(cmp(&torrent(1, 10, 0), &torrent(2, 9, 1)), Greater);
+        assert_eq!(cmp(&torrent(1, 0, 0), &torrent(2, 0, 5)), Equal);
+        assert_eq!(cmp(&torrent(1, 1, 3), &torrent(2, 2, 5)), Less);
+        assert_eq!(
+            cmp(&torrent(1, u32::MAX, 1), &torrent(2, u32::MAX, 2)),
+            Greater
+        );
+    }
+
+    #[test]
+    fn refresh_keeps_the_selection() {
+        let mut table = TableData::default();
+        table.load(vec![torrent(1, 5, 1), torrent(2, 50, 1), torrent(3, 20, 1)]);
+        assert_eq!(table.state.selected(), Some(0));
+
+        table.state.select(Some(2));
+        table.toggle_local_sort(LocalSort::Seeders);
+        assert_eq!(
+            links(&table),
+            [
+                "https://nyaa.si/view/2",
+                "https://nyaa.si/view/3",
+                "https://nyaa.si/view/1",
+            ]
+        );
+        assert_eq!(table.selected().map(|t| t.seeders), Some(20));
+
+        table.toggle_local_sort(LocalSort::Seeders);
+        table.toggle_local_sort(LocalSort::Seeders);
+        assert_eq!(links(&table)[2], "https://nyaa.si/view/3");
+        assert_eq!(table.selected().map(|t| t.seeders), Some(20));
+
+        // filtered out, so the first row is selected instead
+        table.page_filter = PageFilter::new("- 02", false);
+        table.refresh();
+        assert_eq!(table.state.selected(), Some(0));
+        assert_eq!(table.selected().map(|t| t.seeders), Some(50));
     }
 }
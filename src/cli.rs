@@ -0,0 +1,223 @@
+use std::io::{self, Write};
+
+use anyhow::{Result, anyhow};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::config::Config;
+use crate::filter::PageFilter;
+use nyaa_rs::client::Backend;
+use nyaa_rs::error::Error;
+use nyaa_rs::model::{Filter, Order, Sort, Torrent};
+use nyaa_rs::release::Release;
+use serde::Serialize;
+
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// search without starting the tui and print the results
+    Search(SearchArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    pub query: String,
+    /// category code, e.g. `1_2` for english-translated anime
+    #[arg(short, long, default_value = "0_0")]
+    pub category: String,
+    /// none, no-remakes or trusted
+    #[arg(short, long, default_value = "none")]
+    pub filter: Filter,
+    /// date, downloads, seeders, leechers, size, comments or name
+    #[arg(short, long, default_value = "date")]
+    pub sort: Sort,
+    /// asc or desc
+    #[arg(short, long, default_value = "desc")]
+    pub order: Order,
+    #[arg(short, long, default_value_t = 1)]
+    pub page: u32,
+    /// name of a site from config.json, defaults to the first one
+    #[arg(long)]
+    pub site: Option<String>,
+    /// html or rss, overrides `backend` from config.json
+    #[arg(short, long)]
+    pub backend: Option<Backend>,
+    /// keep only results matching this, same syntax as `/` in the tui,
+    /// e.g. `group:subsplease res:1080 ep:12`
+    #[arg(short, long = "match", value_name = "FILTER")]
+    pub matching: Option<String>,
+    /// also print the group, episode, resolution and so on parsed from each title
+    #[arg(short, long)]
+    pub release: bool,
+    #[arg(long, value_enum, default_value_t = Format::Table)]
+    pub format: Format,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Format {
+    Json,
+    Ndjson,
+    Csv,
+    Tsv,
+    Table,
+}
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Search(args) => search(args).await,
+    }
+}
+
+async fn search(args: SearchArgs) -> Result<()> {
+    let config = Config::load()?;
+    let mut client = config.client();
+    if let Some(name) = &args.site {
+        let site = config
+            .sites()
+            .into_iter()
+            .find(|s| s.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| anyhow!("unknown site: {name}"))?;
+        client = client.with_site(site);
+    }
+    if let Some(backend) = args.backend {
+        client = client.with_backend(backend);
+    }
+    let category = client
+        .site()
+        .kind
+        .category(&args.category)
+        .ok_or_else(|| anyhow!("unknown category: {}", args.category))?;
+
+    let torrents = match client
+        .search(
+            &args.query,
+            category,
+            args.filter,
+            args.sort,
+            args.order,
+            args.page,
+        )
+        .await
+    {
+        Err(Error::EmptyPage) => Vec::new(),
+        result => result?,
+    };
+    let mut rows: Vec<Row> = torrents
+        .iter()
+        .map(|torrent| Row {
+            torrent,
+            release: torrent.release(),
+        })
+        .collect();
+    if let Some(text) = &args.matching {
+        let filter = PageFilter::new(text, false);
+        rows.retain(|row| filter.matches(&row.torrent.title, &row.release).is_some());
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    match args.format {
+        Format::Json => {
+            let json: Vec<_> = rows.iter().map(|row| row.json(args.release)).collect();
+            serde_json::to_writer_pretty(&mut out, &json)?;
+            writeln!(out)?;
+        }
+        Format::Ndjson => {
+            for row in &rows {
+                serde_json::to_writer(&mut out, &row.json(args.release))?;
+                writeln!(out)?;
+            }
+        }
+        Format::Csv => write_delimited(&mut out, &rows, args.release, b',')?,
+        Format::Tsv => write_delimited(&mut out, &rows, args.release, b'\t')?,
+        Format::Table => write_table(&mut out, &rows, args.release)?,
+    }
+    Ok(())
+}
+
+struct Row<'a> {
+    torrent: &'a Torrent,
+    release: Release,
+}
+
+/// a torrent as json, with its parsed title under `release` when asked for
+#[derive(Serialize)]
+struct JsonRow<'a> {
+    #[serde(flatten)]
+    torrent: &'a Torrent,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    release: Option<&'a Release>,
+}
+
+impl Row<'_> {
+    fn json(&self, release: bool) -> JsonRow<'_> {
+        JsonRow {
+            torrent: self.torrent,
+            release: release.then_some(&self.release),
+        }
+    }
+}
+
+fn write_delimited(out: &mut impl Write, rows: &[Row], release: bool, delimiter: u8) -> Result<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(out);
+    for row in rows {
+        // csv can't flatten, a tuple of structs gets the columns of both
+        if release {
+            writer.serialize((row.torrent, &row.release))?;
+        } else {
+            writer.serialize(row.torrent)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_table(out: &mut impl Write, rows: &[Row], release: bool) -> Result<()> {
+    let columns = |group: &str, episode: &str, resolution: &str| {
+        if release {
+            format!("{group:<14.14}  {episode:<8}  {resolution:>5}  ")
+        } else {
+            String::new()
+        }
+    };
+    writeln!(
+        out,
+        "{:<16}  {}{:>10}  {:>6}  {:>6}  {:>7}  title",
+        "date",
+        columns("group", "ep", "res"),
+        "size",
+        "seed",
+        "leech",
+        "dls"
+    )?;
+    for Row {
+        torrent: t,
+        release: r,
+    } in rows
+    {
+        let resolution = r.resolution.map(|r| format!("{r}p")).unwrap_or_default();
+        writeln!(
+            out,
+            "{:<16}  {}{:>10}  {:>6}  {:>6}  {:>7}  {}",
+            t.date.format("%Y-%m-%d %H:%M"),
+            columns(
+                r.group.as_deref().unwrap_or_default(),
+                &r.episode_label(),
+                &resolution
+            ),
+            t.size,
+            t.seeders,
+            t.leechers,
+            t.downloads,
+            t.title
+        )?;
+    }
+    Ok(())
+}
@@ -1,11 +1,22 @@
+//! fetching and scraping nyaa.si pages, or any other nyaav2 site
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::OnceLock;
 use std::time::Duration;
 
-use anyhow::{Context, Result};
-use reqwest::Client as HttpClient;
+use chrono::{DateTime, NaiveDateTime};
+use reqwest::header::{HeaderMap, SERVER};
+use reqwest::{Client as HttpClient, Response, StatusCode};
 use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
 
-use crate::model::{Category, Sort, Torrent};
+use crate::error::{Error, ParseError, Result};
+use crate::model::{
+    Category, Comment, FileEntry, Filter, Order, Sort, Torrent, TorrentDetails, TorrentStatus,
+};
+use crate::site::Site;
 
 static ITEM_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
@@ -16,13 +27,73 @@ static DATE_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static SEEDERS_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static LEECHERS_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static DOWNLOADS_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static COMMENTS_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static INFO_LABEL_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static INFO_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static INFO_HASH_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static FILE_LIST_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static COMMENT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static COMMENT_AUTHOR_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static COMMENT_USER_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static COMMENT_DATE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static COMMENT_BODY_SELECTOR: OnceLock<Selector> = OnceLock::new();
 
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
-const BASE_URL: &str = "https://nyaa.si";
 
+mod rss;
+
+/// where search results are read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// scrape the html listing
+    #[default]
+    Html,
+    /// read the `?page=rss` feed. it ignores the page, sort and order and
+    /// always lists the newest uploads, so anything else is scraped instead
+    Rss,
+}
+
+impl Backend {
+    /// whether this backend can fetch the page asked for
+    fn serves(self, sort: Sort, order: Order, page: u32) -> bool {
+        match self {
+            Self::Html => true,
+            Self::Rss => sort == Sort::Date && order == Order::Descending && page == 1,
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Html => write!(f, "html"),
+            Self::Rss => write!(f, "rss"),
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "html" => Ok(Self::Html),
+            "rss" => Ok(Self::Rss),
+            other => Err(ParseError::new("backend", other)),
+        }
+    }
+}
+
+/// an http client for nyaa.si, cheap to clone
 #[derive(Debug, Clone)]
 pub struct Client {
     http: HttpClient,
+    site: Site,
+    backend: Backend,
+    fallback: Option<Backend>,
 }
 
 impl Default for Client {
@@ -32,6 +103,9 @@ impl Default for Client {
                 .timeout(REQUEST_TIMEOUT)
                 .build()
                 .unwrap_or_default(),
+            site: Site::default(),
+            backend: Backend::default(),
+            fallback: None,
         }
     }
 }
@@ -41,31 +115,270 @@ impl Client {
         Self::default()
     }
 
+    /// searches `site` instead of nyaa.si
+    pub fn with_site(mut self, site: Site) -> Self {
+        self.site = site;
+        self
+    }
+
+    pub fn site(&self) -> &Site {
+        &self.site
+    }
+
+    /// reads search results from `backend` instead of the html listing
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// retries with `fallback` when the backend fails or finds nothing
+    pub fn with_fallback(mut self, fallback: Option<Backend>) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// fetches one page of search results, rows that fail to parse are skipped
     pub async fn search(
         &self,
         query: &str,
         category: Category,
+        filter: Filter,
         sort: Sort,
+        order: Order,
         page: u32,
     ) -> Result<Vec<Torrent>> {
         let encoded_query = urlencoding::encode(query);
-        let url =
-            format!("{BASE_URL}/?f=0&c={category}&q={encoded_query}&s={sort}&o=desc&p={page}");
-        let response = self.http.get(&url).send().await?.text().await?;
+        let params =
+            format!("f={filter}&c={category}&q={encoded_query}&s={sort}&o={order}&p={page}");
+
+        let backend = if self.backend.serves(sort, order, page) {
+            self.backend
+        } else {
+            Backend::Html
+        };
+        let results = self.fetch(backend, &params).await;
+        let fallback = self
+            .fallback
+            .filter(|b| *b != backend && b.serves(sort, order, page));
+        match fallback {
+            Some(fallback) if results.is_err() => self.fetch(fallback, &params).await,
+            _ => results,
+        }
+    }
+
+    /// tries each of the site's urls in turn until one answers
+    async fn fetch(&self, backend: Backend, params: &str) -> Result<Vec<Torrent>> {
+        let mut last_error = None;
+        for base_url in &self.site.urls {
+            match self.fetch_from(base_url, backend, params).await {
+                Err(e) if e.is_unavailable() => last_error = Some(e),
+                result => return result,
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            Error::Malformed(format!("{} has no urls configured", self.site.name))
+        }))
+    }
+
+    async fn fetch_from(
+        &self,
+        base_url: &str,
+        backend: Backend,
+        params: &str,
+    ) -> Result<Vec<Torrent>> {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let url = match backend {
+            Backend::Html => format!("{base_url}/?{params}"),
+            Backend::Rss => format!("{base_url}/?page=rss&{params}"),
+        };
+        let response = self.get_text(&url).await?;
+
+        match backend {
+            Backend::Html => parse(move || extract(&response, &base_url)).await,
+            Backend::Rss => parse(move || rss::extract(&response)).await,
+        }
+    }
+
+    /// sends a get and turns error statuses and challenge pages into errors
+    async fn get(&self, url: &str) -> Result<Response> {
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| Error::request(url, e))?;
 
-        tokio::task::spawn_blocking(move || extract(&response)).await?
+        let status = response.status();
+        if is_challenge(status, response.headers()) {
+            return Err(Error::Challenge {
+                url: url.to_string(),
+            });
+        }
+        if !status.is_success() {
+            return Err(Error::Status {
+                url: url.to_string(),
+                status,
+            });
+        }
+        Ok(response)
+    }
+
+    async fn get_text(&self, url: &str) -> Result<String> {
+        let body = self
+            .get(url)
+            .await?
+            .text()
+            .await
+            .map_err(|e| Error::request(url, e))?;
+
+        if has_challenge_title(&body) {
+            return Err(Error::Challenge {
+                url: url.to_string(),
+            });
+        }
+        Ok(body)
+    }
+
+    /// saves the .torrent file into `dir` and returns the path it was written to
+    pub async fn download_torrent(&self, torrent: &Torrent, dir: &Path) -> Result<PathBuf> {
+        let bytes = self
+            .get(&torrent.torrent_url)
+            .await?
+            .bytes()
+            .await
+            .map_err(|e| Error::request(&torrent.torrent_url, e))?;
+
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|source| Error::Io {
+                path: dir.to_path_buf(),
+                source,
+            })?;
+        let path = dir.join(format!("{}.torrent", sanitize_filename(&torrent.title)));
+        tokio::fs::write(&path, &bytes)
+            .await
+            .map_err(|source| Error::Io {
+                path: path.clone(),
+                source,
+            })?;
+
+        Ok(path)
+    }
+
+    /// scrapes the torrent's view page for its description, files and comments
+    pub async fn details(&self, torrent: &Torrent) -> Result<TorrentDetails> {
+        let response = self.get_text(&torrent.link).await?;
+
+        parse(move || extract_details(&response)).await
+    }
+}
+
+/// in bytes, filesystems cap names at 255 and `.torrent` still goes on the end
+const MAX_FILENAME_LEN: usize = 200;
+
+fn sanitize_filename(name: &str) -> String {
+    let mut cleaned = String::new();
+    for c in name.chars().map(|c| match c {
+        '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+        c if c.is_control() => '_',
+        c => c,
+    }) {
+        if cleaned.len() + c.len_utf8() > MAX_FILENAME_LEN {
+            break;
+        }
+        cleaned.push(c);
+    }
+    let cleaned = cleaned.trim_matches(|c: char| c == '.' || c.is_whitespace());
+
+    if cleaned.is_empty() {
+        "torrent".to_string()
+    } else {
+        cleaned.to_string()
     }
 }
 
-fn extract(html: &str) -> Result<Vec<Torrent>> {
+/// cloudflare marks its challenges with a header, ddos-guard only with its
+/// server name on a 403
+fn is_challenge(status: StatusCode, headers: &HeaderMap) -> bool {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_ascii_lowercase()
+    };
+    let server = header(SERVER.as_str());
+
+    header("cf-mitigated") == "challenge"
+        || (matches!(status.as_u16(), 403 | 503)
+            && (server.contains("ddos-guard") || server.contains("cloudflare")))
+}
+
+/// the js challenges come back as 200 with a telltale title
+fn has_challenge_title(body: &str) -> bool {
+    let head = String::from_utf8_lossy(&body.as_bytes()[..body.len().min(4096)]).to_lowercase();
+    head.contains("<title>ddos-guard</title>") || head.contains("<title>just a moment...</title>")
+}
+
+/// runs a parser off the async threads, passing on any panic
+async fn parse<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+}
+
+/// keeps the rows that parsed; a page where none did fails with the first
+/// row's error, one with no rows at all is `EmptyPage`
+pub(crate) fn collect_rows<I>(rows: I) -> Result<Vec<Torrent>>
+where
+    I: Iterator<Item = Result<Torrent, String>>,
+{
+    let mut torrents = Vec::new();
+    let mut first_error = None;
+    for (row, result) in rows.enumerate() {
+        match result {
+            Ok(torrent) => torrents.push(torrent),
+            Err(reason) => {
+                first_error.get_or_insert(Error::Parse { row, reason });
+            }
+        }
+    }
+
+    match first_error {
+        _ if !torrents.is_empty() => Ok(torrents),
+        Some(e) => Err(e),
+        None => Err(Error::EmptyPage),
+    }
+}
+
+/// `1.4 GiB` to bytes, nyaa always uses binary units
+pub(crate) fn parse_size(size: &str) -> Option<u64> {
+    let (number, unit) = size.trim().split_once(' ')?;
+    let number: f64 = number.parse().ok()?;
+    let multiplier: u64 = match unit {
+        "B" | "Bytes" => 1,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        "PiB" => 1 << 50,
+        _ => return None,
+    };
+    Some((number * multiplier as f64).round() as u64)
+}
+
+fn extract(html: &str, base_url: &str) -> Result<Vec<Torrent>> {
     let document = Html::parse_document(html);
     let selector = ITEM_SELECTOR.get_or_init(|| Selector::parse("table>tbody>tr").unwrap());
 
     document
         .select(selector)
-        .filter_map(|item| extract_torrent(item).ok())
-        .collect::<Vec<_>>()
-        .pipe(Ok)
+        .map(|item| extract_torrent(item, base_url))
+        .pipe(collect_rows)
 }
 
 impl<T> Pipe for T {}
@@ -79,21 +392,25 @@ trait Pipe: Sized {
     }
 }
 
-fn extract_torrent(item: ElementRef) -> Result<Torrent> {
-    let title = extract_text(
-        item,
-        TITLE_SELECTOR
-            .get_or_init(|| Selector::parse("td:nth-of-type(2)>a:not(.comments)").unwrap()),
-    )?;
+fn extract_torrent(item: ElementRef, base_url: &str) -> Result<Torrent, String> {
+    let title_sel = TITLE_SELECTOR
+        .get_or_init(|| Selector::parse("td:nth-of-type(2)>a:not(.comments)").unwrap());
+    let title = extract_text(item, title_sel).ok_or("title not found")?;
+    let view_path = item
+        .select(title_sel)
+        .next()
+        .and_then(|el| el.value().attr("href"))
+        .ok_or("view link not found")?;
+    let link = format!("{base_url}{view_path}");
 
     let link_sel =
         LINK_SELECTOR.get_or_init(|| Selector::parse("td:nth-of-type(3)>a:first-child").unwrap());
-    let link_path = item
+    let torrent_path = item
         .select(link_sel)
         .next()
         .and_then(|el| el.value().attr("href"))
-        .context("link not found")?;
-    let link = format!("{BASE_URL}{link_path}");
+        .ok_or("torrent link not found")?;
+    let torrent_url = format!("{base_url}{torrent_path}");
 
     let magnet_sel = MAGNET_SELECTOR
         .get_or_init(|| Selector::parse("td:nth-of-type(3)>a:nth-child(2)").unwrap());
@@ -107,16 +424,28 @@ fn extract_torrent(item: ElementRef) -> Result<Torrent> {
     let size = extract_text(
         item,
         SIZE_SELECTOR.get_or_init(|| Selector::parse("td:nth-of-type(4)").unwrap()),
-    )?;
-
-    let mut date = extract_text(
-        item,
-        DATE_SELECTOR.get_or_init(|| Selector::parse("td:nth-of-type(5)").unwrap()),
-    )?;
+    )
+    .ok_or("size not found")?;
+    // a size we can't read shouldn't cost the row, it just sorts as empty
+    let size_bytes = parse_size(&size).unwrap_or(0);
 
-    if date.len() > 10 {
-        date.truncate(10);
-    }
+    let date_cell = item
+        .select(DATE_SELECTOR.get_or_init(|| Selector::parse("td:nth-of-type(5)").unwrap()))
+        .next()
+        .ok_or("date not found")?;
+    let date = date_cell
+        .value()
+        .attr("data-timestamp")
+        .and_then(|ts| ts.parse().ok())
+        .and_then(|ts| DateTime::from_timestamp(ts, 0))
+        .or_else(|| {
+            // the shown text is utc too, only used if the attribute ever goes away
+            let text = date_cell.text().collect::<String>();
+            NaiveDateTime::parse_from_str(text.trim(), "%Y-%m-%d %H:%M")
+                .ok()
+                .map(|naive| naive.and_utc())
+        })
+        .ok_or("date not found")?;
 
     let seeders = extract_u32(
         item,
@@ -131,32 +460,420 @@ fn extract_torrent(item: ElementRef) -> Result<Torrent> {
         DOWNLOADS_SELECTOR.get_or_init(|| Selector::parse("td:nth-of-type(8)").unwrap()),
     );
 
+    let comments = extract_u32(
+        item,
+        COMMENTS_SELECTOR.get_or_init(|| Selector::parse("td:nth-of-type(2)>a.comments").unwrap()),
+    );
+
+    let status = item
+        .value()
+        .classes()
+        .map(TorrentStatus::from_class)
+        .find(|s| *s != TorrentStatus::Normal)
+        .unwrap_or_default();
+
     Ok(Torrent {
         title,
         link,
+        torrent_url,
         magnet_url,
         date,
         seeders,
         leechers,
         downloads,
         size,
+        size_bytes,
+        comments,
+        status,
     })
 }
 
-fn extract_text(item: ElementRef, selector: &Selector) -> Result<String> {
-    Ok(item
-        .select(selector)
+fn extract_text(item: ElementRef, selector: &Selector) -> Option<String> {
+    item.select(selector)
         .next()
-        .context("element not found")?
-        .text()
-        .collect::<String>()
-        .trim()
-        .to_string())
+        .map(|el| el.text().collect::<String>().trim().to_string())
 }
 
 fn extract_u32(item: ElementRef, selector: &Selector) -> u32 {
     extract_text(item, selector)
-        .ok()
         .and_then(|s| s.parse().ok())
         .unwrap_or(0)
 }
+
+fn extract_details(html: &str) -> Result<TorrentDetails> {
+    let document = Html::parse_document(html);
+
+    // the info panel is a grid of `label: value` column pairs
+    let label_sel = INFO_LABEL_SELECTOR.get_or_init(|| Selector::parse("div.col-md-1").unwrap());
+    let field = |label: &str| {
+        document
+            .select(label_sel)
+            .find(|el| el.text().collect::<String>().trim() == label)
+            .and_then(|el| el.next_siblings().find_map(ElementRef::wrap))
+    };
+    let text_of = |el: ElementRef| el.text().collect::<String>().trim().to_string();
+
+    let submitter = field("Submitter:")
+        .map(text_of)
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "Anonymous".to_string());
+
+    let information = field("Information:")
+        .and_then(|el| {
+            el.select(INFO_LINK_SELECTOR.get_or_init(|| Selector::parse("a").unwrap()))
+                .next()
+                .and_then(|a| a.value().attr("href"))
+                .map(str::to_string)
+        })
+        .filter(|s| !s.is_empty());
+
+    let timestamp = field("Date:").map(text_of).unwrap_or_default();
+
+    let info_hash = document
+        .select(INFO_HASH_SELECTOR.get_or_init(|| Selector::parse("kbd").unwrap()))
+        .next()
+        .map(text_of)
+        .ok_or_else(|| Error::Malformed("info hash not found".to_string()))?;
+
+    let description = document
+        .select(
+            DESCRIPTION_SELECTOR.get_or_init(|| Selector::parse("#torrent-description").unwrap()),
+        )
+        .next()
+        .map(text_of)
+        .unwrap_or_default();
+
+    let files = document
+        .select(
+            FILE_LIST_SELECTOR.get_or_init(|| Selector::parse("div.torrent-file-list>ul").unwrap()),
+        )
+        .next()
+        .map(extract_files)
+        .unwrap_or_default();
+
+    let comments = document
+        .select(COMMENT_SELECTOR.get_or_init(|| Selector::parse("div.comment-panel").unwrap()))
+        .filter_map(extract_comment)
+        .collect();
+
+    Ok(TorrentDetails {
+        submitter,
+        information,
+        info_hash,
+        timestamp,
+        description,
+        files,
+        comments,
+    })
+}
+
+fn extract_comment(panel: ElementRef) -> Option<Comment> {
+    let author_block = panel
+        .select(COMMENT_AUTHOR_SELECTOR.get_or_init(|| Selector::parse("div.col-md-2>p").unwrap()))
+        .next()?;
+    let user = author_block
+        .select(COMMENT_USER_SELECTOR.get_or_init(|| Selector::parse("a").unwrap()))
+        .next();
+
+    let author = user
+        .map(|a| a.text().collect::<String>().trim().to_string())
+        .unwrap_or_else(|| "Anonymous".to_string());
+    let trusted = user.is_some_and(|a| {
+        has_class(a, "text-success") || a.value().attr("title") == Some("Trusted")
+    });
+    let uploader = author_block.text().any(|t| t.contains("(uploader)"));
+
+    let timestamp = panel
+        .select(COMMENT_DATE_SELECTOR.get_or_init(|| Selector::parse("small").unwrap()))
+        .next()
+        .map(|el| el.text().collect::<String>().trim().to_string())
+        .unwrap_or_default();
+
+    let body = panel
+        .select(
+            COMMENT_BODY_SELECTOR.get_or_init(|| Selector::parse("div.comment-content").unwrap()),
+        )
+        .next()?
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string();
+
+    Some(Comment {
+        author,
+        timestamp,
+        body,
+        trusted,
+        uploader,
+    })
+}
+
+fn extract_files(list: ElementRef) -> Vec<FileEntry> {
+    list.children()
+        .filter_map(ElementRef::wrap)
+        .filter(|el| el.value().name() == "li")
+        .map(|li| {
+            let children = li.children().filter_map(ElementRef::wrap);
+
+            if let Some(folder) = children.clone().find(|el| has_class(*el, "folder")) {
+                return FileEntry {
+                    name: folder.text().collect::<String>().trim().to_string(),
+                    size: None,
+                    children: children
+                        .filter(|el| el.value().name() == "ul")
+                        .flat_map(extract_files)
+                        .collect(),
+                };
+            }
+
+            let name = li
+                .children()
+                .filter_map(|n| n.value().as_text())
+                .map(|t| t.trim())
+                .collect::<String>();
+            let size = children
+                .filter(|el| has_class(*el, "file-size"))
+                .map(|el| el.text().collect::<String>())
+                .collect::<String>()
+                .trim_matches(|c: char| c == '(' || c == ')' || c.is_whitespace())
+                .to_string();
+
+            FileEntry {
+                name,
+                size: Some(size),
+                children: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+fn has_class(el: ElementRef, class: &str) -> bool {
+    el.value().classes().any(|c| c == class)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LISTING: &str = include_str!("../tests/fixtures/listing.html");
+    const VIEW: &str = include_str!("../tests/fixtures/view.html");
+    const FEED: &str = include_str!("../tests/fixtures/feed.xml");
+
+    async fn search_rss(sort: Sort, order: Order, page: u32, feed: bool) {
+        let mut server = mockito::Server::new_async().await;
+        let rss = server
+            .mock("GET", "/")
+            .match_query(mockito::Matcher::UrlEncoded("page".into(), "rss".into()))
+            .with_body(FEED)
+            .expect(usize::from(feed))
+            .create_async()
+            .await;
+        let html = server
+            .mock("GET", "/")
+            .match_query(mockito::Matcher::UrlEncoded("p".into(), page.to_string()))
+            .with_body(LISTING)
+            .expect(usize::from(!feed))
+            .create_async()
+            .await;
+
+        let client = Client::new()
+            .with_site(Site::new("mock", vec![server.url()], Default::default()))
+            .with_backend(Backend::Rss);
+        let torrents = client
+            .search(
+                "frieren",
+                Category::All,
+                Filter::NoFilter,
+                sort,
+                order,
+                page,
+            )
+            .await
+            .unwrap();
+        assert!(!torrents.is_empty());
+        rss.assert_async().await;
+        html.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn rss_serves_the_newest_first_page() {
+        search_rss(Sort::Date, Order::Descending, 1, true).await;
+    }
+
+    #[tokio::test]
+    async fn rss_falls_back_to_html_for_other_pages_and_sorts() {
+        search_rss(Sort::Date, Order::Descending, 2, false).await;
+        search_rss(Sort::Seeders, Order::Descending, 1, false).await;
+        search_rss(Sort::Date, Order::Ascending, 1, false).await;
+    }
+
+    #[test]
+    fn parses_listing_rows_and_skips_broken_ones() {
+        let torrents = extract(LISTING, "https://nyaa.si").unwrap();
+        assert_eq!(torrents.len(), 3);
+
+        let trusted = &torrents[0];
+        assert_eq!(
+            trusted.title,
+            "[SubsPlease] Sousou no Frieren - 12 (1080p) [ABCD1234].mkv"
+        );
+        assert_eq!(trusted.link, "https://nyaa.si/view/1234567");
+        assert_eq!(
+            trusted.torrent_url,
+            "https://nyaa.si/download/1234567.torrent"
+        );
+        assert_eq!(
+            trusted.magnet_url,
+            "magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567&dn=Frieren"
+        );
+        assert_eq!(
+            trusted.date,
+            DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+        );
+        assert_eq!(
+            (trusted.seeders, trusted.leechers, trusted.downloads),
+            (1234, 56, 7890)
+        );
+        assert_eq!(trusted.size, "1.4 GiB");
+        assert_eq!(trusted.size_bytes, 1_503_238_554);
+        assert_eq!(trusted.comments, 3);
+        assert_eq!(trusted.status, TorrentStatus::Trusted);
+
+        // no magnet, no comments and only the date text to go by
+        let remake = &torrents[1];
+        assert_eq!(remake.magnet_url, "");
+        assert_eq!(remake.comments, 0);
+        assert_eq!(remake.date.to_rfc3339(), "2023-11-15T08:30:00+00:00");
+        assert_eq!(remake.size_bytes, 700 << 20);
+        assert_eq!(remake.status, TorrentStatus::Remake);
+
+        let odd_size = &torrents[2];
+        assert_eq!(odd_size.size, "1.4 GB");
+        assert_eq!(odd_size.size_bytes, 0);
+    }
+
+    #[test]
+    fn parses_the_view_page() {
+        let details = extract_details(VIEW).unwrap();
+        assert_eq!(details.submitter, "subsplease");
+        assert_eq!(
+            details.information.as_deref(),
+            Some("https://subsplease.org/")
+        );
+        assert_eq!(
+            details.info_hash,
+            "0123456789abcdef0123456789abcdef01234567"
+        );
+        assert_eq!(details.timestamp, "2023-11-14 22:13 UTC");
+        assert_eq!(
+            details.description,
+            "Episode 12, see the site for the rest."
+        );
+
+        assert_eq!(
+            details.files,
+            vec![
+                FileEntry {
+                    name: "Frieren".to_string(),
+                    size: None,
+                    children: vec![FileEntry {
+                        name: "Sousou no Frieren - 12.mkv".to_string(),
+                        size: Some("1.4 GiB".to_string()),
+                        children: Vec::new(),
+                    }],
+                },
+                FileEntry {
+                    name: "readme.txt".to_string(),
+                    size: Some("1.0 KiB".to_string()),
+                    children: Vec::new(),
+                },
+            ]
+        );
+
+        assert_eq!(
+            details.comments,
+            vec![
+                Comment {
+                    author: "subsplease".to_string(),
+                    timestamp: "2023-11-14 23:13 UTC".to_string(),
+                    body: "batch next week".to_string(),
+                    trusted: true,
+                    uploader: true,
+                },
+                Comment {
+                    author: "viewer".to_string(),
+                    timestamp: "2023-11-15 00:13 UTC".to_string(),
+                    body: "thanks!".to_string(),
+                    trusted: false,
+                    uploader: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_view_page_without_an_info_hash_is_malformed() {
+        let err = extract_details("<html><body></body></html>").unwrap_err();
+        assert!(matches!(err, Error::Malformed(_)), "{err}");
+    }
+
+    #[test]
+    fn parses_sizes() {
+        let cases = [
+            ("512 Bytes", Some(512)),
+            ("1 B", Some(1)),
+            ("1.0 KiB", Some(1024)),
+            ("700.0 MiB", Some(700 << 20)),
+            ("1.4 GiB", Some(1_503_238_554)),
+            ("2 TiB", Some(2 << 40)),
+            ("1 PiB", Some(1 << 50)),
+            ("1.4GiB", None),
+            ("1.4 GB", None),
+            ("", None),
+        ];
+        for (size, expected) in cases {
+            assert_eq!(parse_size(size), expected, "{size}");
+        }
+    }
+
+    #[test]
+    fn collects_rows() {
+        let ok = || Ok(extract(LISTING, "").unwrap().remove(0));
+        let err = |reason: &str| Err(reason.to_string());
+
+        let torrents = collect_rows([err("title not found"), ok()].into_iter()).unwrap();
+        assert_eq!(torrents.len(), 1);
+
+        let all_broken = collect_rows([err("title not found"), err("size not found")].into_iter());
+        assert!(matches!(
+            all_broken,
+            Err(Error::Parse { row: 0, ref reason }) if reason == "title not found"
+        ));
+
+        assert!(matches!(
+            collect_rows(std::iter::empty()),
+            Err(Error::EmptyPage)
+        ));
+        assert!(matches!(
+            extract("<table><tbody></tbody></table>", "https://nyaa.si"),
+            Err(Error::EmptyPage)
+        ));
+    }
+
+    #[test]
+    fn sanitizes_filenames() {
+        assert_eq!(
+            sanitize_filename("[Group] a/b: c?*<>|\"d\\e\tf.mkv"),
+            "[Group] a_b_ c______d_e_f.mkv"
+        );
+        assert_eq!(sanitize_filename("  ..name..  "), "name");
+        assert_eq!(sanitize_filename(""), "torrent");
+        assert_eq!(sanitize_filename(" ... "), "torrent");
+
+        let japanese = "【漫画】葬送のフリーレン".repeat(10);
+        let name = sanitize_filename(&japanese);
+        assert!(name.len() <= MAX_FILENAME_LEN, "{} bytes", name.len());
+        assert!(japanese.starts_with(&name));
+        assert!(name.len() > MAX_FILENAME_LEN - 4);
+    }
+}
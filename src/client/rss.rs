@@ -0,0 +1,179 @@
+//! parsing the `?page=rss` feed, which carries the same fields as the html
+//! listing in its `nyaa:` namespace and changes far less often
+
+use chrono::{DateTime, Utc};
+use roxmltree::{Document, Node};
+
+use super::{collect_rows, parse_size};
+use crate::error::{Error, Result};
+use crate::model::{Torrent, TorrentStatus};
+
+/// the feed only has the info hash, these are the trackers nyaa puts in its magnets
+const TRACKERS: [&str; 5] = [
+    "http://nyaa.tracker.wf:7777/announce",
+    "udp://open.stealth.si:80/announce",
+    "udp://tracker.opentrackr.org:1337/announce",
+    "udp://exodus.desync.com:6969/announce",
+    "udp://tracker.torrent.eu.org:451/announce",
+];
+
+pub(super) fn extract(xml: &str) -> Result<Vec<Torrent>> {
+    let document =
+        Document::parse(xml).map_err(|e| Error::Malformed(format!("invalid feed: {e}")))?;
+
+    collect_rows(
+        document
+            .descendants()
+            .filter(|node| node.has_tag_name("item"))
+            .map(extract_torrent),
+    )
+}
+
+fn extract_torrent(item: Node) -> Result<Torrent, String> {
+    let title = child_text(item, "title")
+        .ok_or("title not found")?
+        .to_string();
+    let torrent_url = child_text(item, "link")
+        .ok_or("torrent link not found")?
+        .to_string();
+    let link = child_text(item, "guid")
+        .ok_or("view link not found")?
+        .to_string();
+
+    let magnet_url = child_text(item, "infoHash")
+        .filter(|hash| !hash.is_empty())
+        .map(|hash| magnet(hash, &title))
+        .unwrap_or_default();
+
+    let date = child_text(item, "pubDate")
+        .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+        .map(|date| date.with_timezone(&Utc))
+        .ok_or("date not found")?;
+
+    let size = child_text(item, "size")
+        .ok_or("size not found")?
+        .to_string();
+    let size_bytes = parse_size(&size).unwrap_or(0);
+
+    let status = if flag(item, "remake") {
+        TorrentStatus::Remake
+    } else if flag(item, "trusted") {
+        TorrentStatus::Trusted
+    } else {
+        TorrentStatus::Normal
+    };
+
+    Ok(Torrent {
+        title,
+        link,
+        torrent_url,
+        magnet_url,
+        date,
+        seeders: child_u32(item, "seeders"),
+        leechers: child_u32(item, "leechers"),
+        downloads: child_u32(item, "downloads"),
+        size,
+        size_bytes,
+        comments: child_u32(item, "comments"),
+        status,
+    })
+}
+
+/// text of the first child with this local name, `nyaa:` prefixed or not
+fn child_text<'a>(item: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    item.children()
+        .find(|child| child.tag_name().name() == name)
+        .and_then(|child| child.text())
+        .map(str::trim)
+}
+
+fn child_u32(item: Node, name: &str) -> u32 {
+    child_text(item, name)
+        .and_then(|text| text.parse().ok())
+        .unwrap_or(0)
+}
+
+fn flag(item: Node, name: &str) -> bool {
+    child_text(item, name).is_some_and(|text| text.eq_ignore_ascii_case("yes"))
+}
+
+fn magnet(hash: &str, title: &str) -> String {
+    let trackers: String = TRACKERS
+        .iter()
+        .map(|tracker| format!("&tr={}", urlencoding::encode(tracker)))
+        .collect();
+    format!(
+        "magnet:?xt=urn:btih:{hash}&dn={}{trackers}",
+        urlencoding::encode(title)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED: &str = include_str!("../../tests/fixtures/feed.xml");
+
+    #[test]
+    fn parses_feed_items_and_skips_broken_ones() {
+        let torrents = extract(FEED).unwrap();
+        assert_eq!(torrents.len(), 3);
+
+        let trusted = &torrents[0];
+        assert_eq!(
+            trusted.title,
+            "[SubsPlease] Sousou no Frieren - 12 (1080p) [ABCD1234].mkv"
+        );
+        assert_eq!(trusted.link, "https://nyaa.si/view/1234567");
+        assert_eq!(
+            trusted.torrent_url,
+            "https://nyaa.si/download/1234567.torrent"
+        );
+        assert!(
+            trusted
+                .magnet_url
+                .starts_with("magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567&dn=")
+        );
+        assert_eq!(trusted.magnet_url.matches("&tr=").count(), TRACKERS.len());
+        assert_eq!(
+            trusted.info_hash().as_deref(),
+            Some("0123456789abcdef0123456789abcdef01234567")
+        );
+        assert_eq!(
+            trusted.date,
+            DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+        );
+        assert_eq!(
+            (trusted.seeders, trusted.leechers, trusted.downloads),
+            (1234, 56, 7890)
+        );
+        assert_eq!(trusted.size, "1.4 GiB");
+        assert_eq!(trusted.size_bytes, 1_503_238_554);
+        assert_eq!(trusted.comments, 3);
+        assert_eq!(trusted.status, TorrentStatus::Trusted);
+
+        // an empty info hash means no magnet, and remake wins over trusted
+        let remake = &torrents[1];
+        assert_eq!(remake.magnet_url, "");
+        assert_eq!(remake.size_bytes, 700 << 20);
+        assert_eq!(remake.status, TorrentStatus::Remake);
+
+        let odd_size = &torrents[2];
+        assert_eq!(odd_size.size, "1.4 GB");
+        assert_eq!(odd_size.size_bytes, 0);
+    }
+
+    #[test]
+    fn a_feed_without_items_is_empty() {
+        let feed = r#"<rss version="2.0"><channel><title>Nyaa</title></channel></rss>"#;
+        assert!(matches!(extract(feed), Err(Error::EmptyPage)));
+    }
+
+    #[test]
+    fn html_is_not_a_feed() {
+        assert!(matches!(
+            extract("<html><body><br></body></html>"),
+            Err(Error::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,47 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use anyhow::Result;
+use crossterm::{clipboard::CopyToClipboard, execute};
+
+/// copies through osc 52 so it also reaches the local clipboard over ssh and
+/// inside tmux (with `set-clipboard on`), optionally piping to wl-copy or
+/// xclip as well
+pub fn copy(text: &str, fallback: bool) -> Result<()> {
+    execute!(io::stdout(), CopyToClipboard::to_clipboard_from(text))?;
+
+    if fallback {
+        copy_with_tool(text);
+    }
+    Ok(())
+}
+
+/// best effort, a missing tool just means osc 52 is all we've got
+fn copy_with_tool(text: &str) {
+    let tool: Option<(&str, &[&str])> = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        Some(("wl-copy", &[]))
+    } else if std::env::var_os("DISPLAY").is_some() {
+        Some(("xclip", &["-selection", "clipboard"]))
+    } else {
+        None
+    };
+    let Some((program, args)) = tool else {
+        return;
+    };
+
+    let Ok(mut child) = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    // xclip keeps running to serve the selection, so reap it off the ui thread
+    // instead of leaving a zombie behind
+    std::thread::spawn(move || child.wait());
+}
@@ -0,0 +1,95 @@
+use crate::group::Preference;
+use crate::sink::SinkConfig;
+use crate::tui::DateFormat;
+use anyhow::{Context, Result};
+use nyaa_rs::client::{Backend, Client};
+use nyaa_rs::site::Site;
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub download_dir: Option<PathBuf>,
+    pub sink: SinkConfig,
+    /// extra sinks that can be picked for a single send
+    pub sinks: Vec<SinkConfig>,
+    /// also copy through wl-copy or xclip when they are available
+    pub clipboard_fallback: bool,
+    /// `html` or `rss`
+    pub backend: Backend,
+    /// tried when the backend errors or returns nothing
+    pub fallback: Option<Backend>,
+    /// sites `S` switches between, the first one is used on startup
+    pub sites: Vec<Site>,
+    /// `utc`, `local` or `relative`
+    pub date_format: DateFormat,
+    /// show the group, episode, resolution, codec and source parsed from titles
+    pub release_columns: bool,
+    /// start in the grouped view
+    pub grouped: bool,
+    /// which release of a group is shown first
+    pub preference: Preference,
+}
+
+impl Config {
+    /// the defaults when there is no config.json, an error when it can't be
+    /// read or parsed
+    pub fn load() -> Result<Self> {
+        let Some(path) = find_file("config.json") else {
+            return Ok(Self::default());
+        };
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// the configured sites, just nyaa.si when there are none. sukebei has to
+    /// be added on purpose
+    pub fn sites(&self) -> Vec<Site> {
+        if self.sites.is_empty() {
+            vec![Site::nyaa()]
+        } else {
+            self.sites.clone()
+        }
+    }
+
+    pub fn client(&self) -> Client {
+        Client::new()
+            .with_site(self.sites().remove(0))
+            .with_backend(self.backend)
+            .with_fallback(self.fallback)
+    }
+
+    /// where .torrent files are saved, falling back to the user's downloads folder
+    pub fn download_dir(&self) -> PathBuf {
+        self.download_dir
+            .clone()
+            .or_else(|| {
+                directories::UserDirs::new()
+                    .and_then(|dirs| dirs.download_dir().map(|d| d.to_path_buf()))
+            })
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+}
+
+/// looks for `name` in the working directory, next to the binary, then in the
+/// platform config directory
+pub fn find_file(name: &str) -> Option<PathBuf> {
+    std::env::current_dir()
+        .ok()
+        .map(|cwd| cwd.join(name))
+        .filter(|p| p.exists())
+        .or_else(|| {
+            std::env::current_exe()
+                .ok()
+                .and_then(|p| p.parent().map(|d| d.join(name)))
+                .filter(|p| p.exists())
+        })
+        .or_else(|| {
+            directories::ProjectDirs::from("com", "nyaa-rs", "nyaa")
+                .map(|proj| proj.config_dir().join(name))
+                .filter(|p| p.exists())
+        })
+}
@@ -0,0 +1,105 @@
+//! everything [`Client`](crate::Client) can fail with
+
+use std::io;
+use std::path::PathBuf;
+
+use reqwest::StatusCode;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{url} timed out")]
+    Timeout { url: String },
+    /// any non-2xx answer, e.g. 404, 429 when rate limited or 503 when the site is down
+    #[error("{url} answered {status}")]
+    Status { url: String, status: StatusCode },
+    /// a ddos-guard or cloudflare page was served instead of the site
+    #[error("{url} answered with a ddos-guard/cloudflare challenge")]
+    Challenge { url: String },
+    /// a listing row, counted from 0, is missing something; only returned
+    /// when no row on the page could be read
+    #[error("could not parse row {row}: {reason}")]
+    Parse { row: usize, reason: String },
+    /// the page or feed does not look like a nyaav2 one at all
+    #[error("unexpected page: {0}")]
+    Malformed(String),
+    /// the search has no results, or the page is past the last one
+    #[error("no results")]
+    EmptyPage,
+    #[error("request failed: {0}")]
+    Request(#[source] reqwest::Error),
+    #[error("could not write {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// a name or code the `FromStr` impls don't know, e.g. an unknown sort
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown {kind}: {value}")]
+pub struct ParseError {
+    /// what was being parsed, e.g. `category`
+    pub kind: &'static str,
+    pub value: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(kind: &'static str, value: &str) -> Self {
+        Self {
+            kind,
+            value: value.to_string(),
+        }
+    }
+}
+
+impl Error {
+    pub(crate) fn request(url: &str, error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Self::Timeout {
+                url: url.to_string(),
+            }
+        } else {
+            Self::Request(error)
+        }
+    }
+
+    /// the http status, if the site answered with an error one
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// worth retrying on another mirror: timeouts, refused connections, 5xx
+    /// and challenge pages
+    pub fn is_unavailable(&self) -> bool {
+        match self {
+            Self::Timeout { .. } | Self::Challenge { .. } => true,
+            Self::Status { status, .. } => status.is_server_error(),
+            Self::Request(e) => e.is_connect(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[tokio::test]
+    async fn request_errors_keep_their_cause() {
+        // nothing listens on port 1
+        let cause = reqwest::get("http://127.0.0.1:1").await.unwrap_err();
+        let error = Error::request("http://127.0.0.1:1", cause);
+        assert!(matches!(error, Error::Request(_)));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn parse_errors_name_what_was_parsed() {
+        let error = "newest".parse::<crate::model::Sort>().unwrap_err();
+        assert_eq!(error, ParseError::new("sort", "newest"));
+        assert_eq!(error.to_string(), "unknown sort: newest");
+    }
+}
@@ -0,0 +1,272 @@
+use nyaa_rs::Release;
+use regex::{Regex, RegexBuilder};
+
+/// the `/` filter over the loaded page, fuzzy unless switched to regex.
+/// in fuzzy mode `key:value` words match parsed release fields instead of
+/// the title, e.g. `group:subsplease res:1080 ep:12`
+#[derive(Debug, Default)]
+pub struct PageFilter {
+    pub text: String,
+    pub regex: bool,
+    compiled: Option<Regex>,
+    /// the field terms of `text`
+    fields: Vec<Field>,
+    /// `text` without its field terms
+    fuzzy: String,
+}
+
+#[derive(Debug)]
+enum Field {
+    Group(String),
+    Resolution(u32),
+    Episode(u32),
+    Season(u32),
+    Codec(String),
+    Source(String),
+    Batch(bool),
+    Version(u32),
+}
+
+impl Field {
+    const KEYS: [&str; 15] = [
+        "group", "g", "res", "r", "ep", "e", "season", "s", "codec", "c", "source", "src", "batch",
+        "version", "v",
+    ];
+
+    fn is_key(term: &str) -> bool {
+        term.split_once(':')
+            .is_some_and(|(key, _)| Self::KEYS.iter().any(|k| key.eq_ignore_ascii_case(k)))
+    }
+
+    fn parse(term: &str) -> Option<Self> {
+        let (key, value) = term.split_once(':')?;
+        let value = value.to_lowercase();
+        let number = |v: &str| v.parse().ok();
+        match key.to_lowercase().as_str() {
+            "group" | "g" if !value.is_empty() => Some(Self::Group(value)),
+            "res" | "r" => number(value.trim_end_matches('p')).map(Self::Resolution),
+            "ep" | "e" => number(&value).map(Self::Episode),
+            "season" | "s" => number(&value).map(Self::Season),
+            "codec" | "c" if !value.is_empty() => Some(Self::Codec(value)),
+            "source" | "src" if !value.is_empty() => Some(Self::Source(value)),
+            "batch" => match value.as_str() {
+                "" | "yes" | "y" | "true" => Some(Self::Batch(true)),
+                "no" | "n" | "false" => Some(Self::Batch(false)),
+                _ => None,
+            },
+            "v" | "version" => number(&value).map(Self::Version),
+            _ => None,
+        }
+    }
+
+    fn matches(&self, release: &Release) -> bool {
+        let contains = |field: &Option<String>, value: &str| {
+            field
+                .as_ref()
+                .is_some_and(|f| f.to_lowercase().contains(value))
+        };
+        match self {
+            Self::Group(group) => contains(&release.group, group),
+            Self::Resolution(res) => release.resolution == Some(*res),
+            Self::Episode(ep) => release.episode.is_some_and(|e| e.contains(*ep)),
+            Self::Season(season) => release.season.unwrap_or(1) == *season,
+            Self::Codec(codec) => {
+                contains(&release.video_codec, codec) || contains(&release.audio_codec, codec)
+            }
+            Self::Source(source) => contains(&release.source, source),
+            Self::Batch(batch) => release.batch == *batch,
+            Self::Version(version) => release.version.unwrap_or(1) == *version,
+        }
+    }
+}
+
+impl PageFilter {
+    pub fn new(text: &str, regex: bool) -> Self {
+        let mut filter = Self {
+            text: text.to_string(),
+            regex,
+            ..Self::default()
+        };
+        filter.compile();
+        filter
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.text.is_empty()
+    }
+
+    /// false while the regex doesn't compile, everything matches until it does
+    pub fn is_valid(&self) -> bool {
+        !self.regex || self.compiled.is_some()
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.text.push(c);
+        self.compile();
+    }
+
+    pub fn pop(&mut self) {
+        self.text.pop();
+        self.compile();
+    }
+
+    pub fn toggle_regex(&mut self) {
+        self.regex = !self.regex;
+        self.compile();
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.compile();
+    }
+
+    fn compile(&mut self) {
+        (self.fields, self.fuzzy) = (Vec::new(), String::new());
+        if !self.regex {
+            for term in self.text.split_whitespace() {
+                match Field::parse(term) {
+                    Some(field) => self.fields.push(field),
+                    // a known key whose value is still being typed
+                    None if Field::is_key(term) => {}
+                    None => self.fuzzy.push_str(term),
+                }
+            }
+        }
+        self.compiled = if self.regex {
+            RegexBuilder::new(&self.text)
+                .case_insensitive(true)
+                .build()
+                .ok()
+        } else {
+            None
+        };
+    }
+
+    /// the char positions to highlight in `title`, or `None` when it is filtered out
+    pub fn matches(&self, title: &str, release: &Release) -> Option<Vec<usize>> {
+        if !self.is_active() {
+            return Some(Vec::new());
+        }
+        if !self.regex {
+            if !self.fields.iter().all(|field| field.matches(release)) {
+                return None;
+            }
+            return fuzzy(&self.fuzzy, title);
+        }
+        let Some(regex) = &self.compiled else {
+            return Some(Vec::new());
+        };
+
+        let mut positions = Vec::new();
+        for m in regex.find_iter(title) {
+            let start = title[..m.start()].chars().count();
+            positions.extend(start..start + m.as_str().chars().count());
+        }
+        (!positions.is_empty() || regex.is_match(title)).then_some(positions)
+    }
+}
+
+/// every non-space char of `pattern` has to appear in `title` in order,
+/// case-insensitively; the leftmost such chars are returned
+fn fuzzy(pattern: &str, title: &str) -> Option<Vec<usize>> {
+    let mut wanted = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .peekable();
+    let mut positions = Vec::new();
+
+    for (i, c) in title.chars().enumerate() {
+        let Some(&next) = wanted.peek() else {
+            break;
+        };
+        if c.to_lowercase().eq(std::iter::once(next)) {
+            positions.push(i);
+            wanted.next();
+        }
+    }
+    wanted.peek().is_none().then_some(positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TITLE: &str = "[SubsPlease] Sousou no Frieren - 12 (1080p) [ABCD1234].mkv";
+    const BATCH: &str = "[Judas] Sousou no Frieren (Season 1) [1080p][HEVC x265 10bit] (Batch)";
+
+    fn matches(filter: &str, regex: bool, title: &str) -> Option<Vec<usize>> {
+        PageFilter::new(filter, regex).matches(title, &Release::parse(title))
+    }
+
+    #[test]
+    fn fuzzy_highlights_the_leftmost_chars() {
+        assert_eq!(fuzzy("sbp", "SubsPlease"), Some(vec![0, 2, 4]));
+        assert_eq!(fuzzy("frn", "Frieren"), Some(vec![0, 1, 6]));
+        assert_eq!(fuzzy("", "Frieren"), Some(Vec::new()));
+        assert_eq!(fuzzy("nerf", "Frieren"), None);
+    }
+
+    #[test]
+    fn fuzzy_ignores_case_and_spaces() {
+        assert!(matches("FRIEREN 1080", false, TITLE).is_some());
+        assert!(matches("sousou   frieren", false, TITLE).is_some());
+        assert!(matches("frieren 720", false, TITLE).is_none());
+        assert_eq!(matches("", false, TITLE), Some(Vec::new()));
+    }
+
+    #[test]
+    fn field_terms_match_the_parsed_release() {
+        assert!(matches("group:subsplease", false, TITLE).is_some());
+        assert!(matches("g:JUDAS", false, TITLE).is_none());
+        assert!(matches("res:1080p", false, TITLE).is_some());
+        assert!(matches("res:720", false, TITLE).is_none());
+        assert!(matches("ep:12", false, TITLE).is_some());
+        assert!(matches("ep:13", false, TITLE).is_none());
+        assert!(matches("batch:", false, BATCH).is_some());
+        assert!(matches("batch:no", false, BATCH).is_none());
+        assert!(matches("batch:no", false, TITLE).is_some());
+
+        // fields and the fuzzy part both have to match, and only the title is highlighted
+        assert_eq!(matches("res:1080 sub", false, TITLE), Some(vec![1, 2, 3]));
+        assert!(matches("res:1080 judas", false, TITLE).is_none());
+    }
+
+    #[test]
+    fn half_typed_fields_are_ignored() {
+        assert_eq!(matches("res:", false, TITLE), Some(Vec::new()));
+        assert_eq!(matches("res:", false, BATCH), Some(Vec::new()));
+        assert!(matches("frieren res:10", false, TITLE).is_none());
+        // not a known key, so part of the fuzzy text
+        assert!(matches("foo:", false, TITLE).is_none());
+    }
+
+    #[test]
+    fn invalid_regexes_match_everything() {
+        let filter = PageFilter::new("frieren (", true);
+        assert!(!filter.is_valid());
+        assert_eq!(
+            filter.matches(TITLE, &Release::parse(TITLE)),
+            Some(Vec::new())
+        );
+
+        assert!(PageFilter::new("frieren", true).is_valid());
+        assert!(PageFilter::new("(", false).is_valid());
+        assert_eq!(
+            matches("frieren - \\d+", true, TITLE).map(|p| p.len()),
+            Some(12)
+        );
+        assert!(matches("^\\[judas\\]", true, TITLE).is_none());
+    }
+
+    #[test]
+    fn positions_count_chars() {
+        let title = "【漫画】葬送のフリーレン 12";
+        assert_eq!(matches("フリ", false, title), Some(vec![7, 8]));
+        assert_eq!(
+            matches("フリーレン", true, title),
+            Some(vec![7, 8, 9, 10, 11])
+        );
+        assert_eq!(matches("12", true, title), Some(vec![13, 14]));
+    }
+}
@@ -0,0 +1,330 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{HashMap, HashSet};
+
+use nyaa_rs::model::{Torrent, TorrentStatus};
+use nyaa_rs::release::Release;
+use serde::Deserialize;
+
+/// a row of the loaded page: the torrent, its parsed title and the
+/// highlighted title chars
+pub type Row<'a> = (&'a Torrent, &'a Release, Vec<usize>);
+
+/// how the grouped view picks the best of near-identical releases
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Preference {
+    /// release groups, best first, matched case-insensitively
+    pub groups: Vec<String>,
+    /// picked over other resolutions, otherwise the highest one wins
+    pub resolution: Option<u32>,
+    /// trusted uploads before the rest
+    pub trusted: bool,
+}
+
+impl Default for Preference {
+    fn default() -> Self {
+        Self {
+            groups: Vec::new(),
+            resolution: None,
+            trusted: true,
+        }
+    }
+}
+
+impl Preference {
+    /// preferred group, then resolution, then trusted, remakes last and the
+    /// most seeded breaking ties
+    pub fn compare(
+        &self,
+        (a, ra): (&Torrent, &Release),
+        (b, rb): (&Torrent, &Release),
+    ) -> Ordering {
+        let rank = |t: &Torrent, r: &Release| {
+            let group = r
+                .group
+                .as_ref()
+                .and_then(|g| self.groups.iter().position(|p| p.eq_ignore_ascii_case(g)))
+                .unwrap_or(self.groups.len());
+            let resolution = self.resolution.is_some() && r.resolution != self.resolution;
+            let trusted = self.trusted && t.status != TorrentStatus::Trusted;
+            (
+                group,
+                resolution,
+                Reverse(r.resolution),
+                trusted,
+                t.status == TorrentStatus::Remake,
+                Reverse(t.seeders),
+            )
+        };
+        rank(a, ra).cmp(&rank(b, rb))
+    }
+}
+
+/// where a row sits in the grouped view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowKind {
+    /// not grouped, or the only release of its series and episode
+    Single,
+    /// the best of a group, followed by the rest while expanded
+    Group {
+        key: String,
+        others: usize,
+        expanded: bool,
+    },
+    /// one of the rest of an expanded group
+    Member { key: String },
+}
+
+/// collapses rows of the same series and episode into groups, best first.
+/// groups stay where their first row was, so the page's order still holds
+pub fn group<'a>(
+    rows: Vec<Row<'a>>,
+    preference: &Preference,
+    expanded: &HashSet<String>,
+) -> Vec<(Row<'a>, RowKind)> {
+    let mut groups: Vec<(String, Vec<Row<'a>>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for row in rows {
+        let key = key(row.0, row.1);
+        match index.get(&key) {
+            Some(&i) => groups[i].1.push(row),
+            None => {
+                index.insert(key.clone(), groups.len());
+                groups.push((key, vec![row]));
+            }
+        }
+    }
+
+    let mut grouped = Vec::new();
+    for (key, mut members) in groups {
+        members.sort_by(|a, b| preference.compare((a.0, a.1), (b.0, b.1)));
+        let others = members.len() - 1;
+        let mut members = members.into_iter();
+        let Some(best) = members.next() else {
+            continue;
+        };
+        if others == 0 {
+            grouped.push((best, RowKind::Single));
+            continue;
+        }
+
+        let is_expanded = expanded.contains(&key);
+        grouped.push((
+            best,
+            RowKind::Group {
+                key: key.clone(),
+                others,
+                expanded: is_expanded,
+            },
+        ));
+        if is_expanded {
+            grouped.extend(members.map(|m| (m, RowKind::Member { key: key.clone() })));
+        }
+    }
+    grouped
+}
+
+/// the series and episode, ignoring case and punctuation. titles that
+/// didn't parse into either are never grouped
+fn key(torrent: &Torrent, release: &Release) -> String {
+    let episode = release.episode_label();
+    if release.series.is_empty() || episode.is_empty() {
+        return torrent.link.clone();
+    }
+    let series: Vec<String> = release
+        .series
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    format!("{} {episode}", series.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::test_torrent;
+
+    fn torrent(id: u32, title: &str, status: TorrentStatus, seeders: u32) -> Torrent {
+        Torrent {
+            title: title.to_string(),
+            link: format!("https://nyaa.si/view/{id}"),
+            status,
+            seeders,
+            ..test_torrent()
+        }
+    }
+
+    /// the view page id
+    fn id(torrent: &Torrent) -> &str {
+        torrent.link.rsplit('/').next().unwrap_or_default()
+    }
+
+    /// the titles of `torrents`, best first
+    fn ranked(preference: &Preference, torrents: &[Torrent]) -> Vec<String> {
+        let releases: Vec<Release> = torrents.iter().map(Torrent::release).collect();
+        let mut rows: Vec<_> = torrents.iter().zip(&releases).collect();
+        rows.sort_by(|a, b| preference.compare(*a, *b));
+        rows.into_iter().map(|(t, _)| t.title.clone()).collect()
+    }
+
+    fn normal(id: u32, title: &str) -> Torrent {
+        torrent(id, title, TorrentStatus::Normal, 10)
+    }
+
+    #[test]
+    fn preferred_groups_come_first() {
+        let preference = Preference {
+            groups: vec!["erai-raws".to_string(), "SubsPlease".to_string()],
+            ..Preference::default()
+        };
+        let torrents = [
+            normal(1, "[Judas] Show - 01 (1080p).mkv"),
+            normal(2, "[SubsPlease] Show - 01 (1080p).mkv"),
+            normal(3, "[Erai-raws] Show - 01 [720p].mkv"),
+        ];
+        assert_eq!(
+            ranked(&preference, &torrents),
+            [
+                "[Erai-raws] Show - 01 [720p].mkv",
+                "[SubsPlease] Show - 01 (1080p).mkv",
+                "[Judas] Show - 01 (1080p).mkv",
+            ]
+        );
+    }
+
+    #[test]
+    fn the_configured_resolution_beats_higher_ones() {
+        let preference = Preference {
+            resolution: Some(720),
+            ..Preference::default()
+        };
+        let torrents = [
+            normal(1, "[A] Show - 01 (2160p).mkv"),
+            normal(2, "[A] Show - 01 (720p).mkv"),
+            normal(3, "[A] Show - 01 (1080p).mkv"),
+        ];
+        assert_eq!(
+            ranked(&preference, &torrents),
+            [
+                "[A] Show - 01 (720p).mkv",
+                "[A] Show - 01 (2160p).mkv",
+                "[A] Show - 01 (1080p).mkv",
+            ]
+        );
+    }
+
+    #[test]
+    fn otherwise_the_highest_resolution_wins() {
+        let torrents = [
+            normal(1, "[A] Show - 01.mkv"),
+            normal(2, "[A] Show - 01 (720p).mkv"),
+            normal(3, "[A] Show - 01 (1080p).mkv"),
+        ];
+        assert_eq!(
+            ranked(&Preference::default(), &torrents),
+            [
+                "[A] Show - 01 (1080p).mkv",
+                "[A] Show - 01 (720p).mkv",
+                "[A] Show - 01.mkv",
+            ]
+        );
+    }
+
+    #[test]
+    fn trusted_first_remakes_last_then_seeders() {
+        let title = "[A] Show - 01 (1080p).mkv";
+        let torrents = [
+            torrent(1, title, TorrentStatus::Remake, 500),
+            torrent(2, title, TorrentStatus::Normal, 5),
+            torrent(3, title, TorrentStatus::Normal, 50),
+            torrent(4, title, TorrentStatus::Trusted, 1),
+        ];
+        let releases: Vec<Release> = torrents.iter().map(Torrent::release).collect();
+        let mut rows: Vec<_> = torrents.iter().zip(&releases).collect();
+
+        rows.sort_by(|a, b| Preference::default().compare(*a, *b));
+        let links: Vec<&str> = rows.iter().map(|(t, _)| id(t)).collect();
+        assert_eq!(links, ["4", "3", "2", "1"]);
+
+        let untrusted = Preference {
+            trusted: false,
+            ..Preference::default()
+        };
+        rows.sort_by(|a, b| untrusted.compare(*a, *b));
+        let links: Vec<&str> = rows.iter().map(|(t, _)| id(t)).collect();
+        assert_eq!(links, ["3", "2", "4", "1"]);
+    }
+
+    fn kinds(torrents: &[Torrent], expanded: &HashSet<String>) -> Vec<(String, RowKind)> {
+        let releases: Vec<Release> = torrents.iter().map(Torrent::release).collect();
+        let rows = torrents
+            .iter()
+            .zip(&releases)
+            .map(|(t, r)| (t, r, Vec::new()))
+            .collect();
+        group(rows, &Preference::default(), expanded)
+            .into_iter()
+            .map(|((t, _, _), kind)| (id(t).to_string(), kind))
+            .collect()
+    }
+
+    #[test]
+    fn unparsed_titles_are_never_merged() {
+        let torrents = [
+            normal(1, "Some Artbook"),
+            normal(2, "Some Artbook"),
+            normal(3, "[A] Show (1080p).mkv"),
+            normal(4, "[B] Show (720p).mkv"),
+        ];
+        let kinds = kinds(&torrents, &HashSet::new());
+        assert_eq!(kinds.len(), 4);
+        assert!(kinds.iter().all(|(_, kind)| *kind == RowKind::Single));
+    }
+
+    #[test]
+    fn expanded_groups_list_their_members_after_the_best() {
+        let torrents = [
+            normal(1, "[A] Show - 01 (720p).mkv"),
+            normal(2, "[A] Other - 01 (1080p).mkv"),
+            normal(3, "[B] show! - 01 (1080p).mkv"),
+            normal(4, "[C] Show - 02 (1080p).mkv"),
+        ];
+        let key = "show 01".to_string();
+
+        let collapsed = kinds(&torrents, &HashSet::new());
+        assert_eq!(
+            collapsed,
+            [
+                (
+                    "3".to_string(),
+                    RowKind::Group {
+                        key: key.clone(),
+                        others: 1,
+                        expanded: false,
+                    }
+                ),
+                ("2".to_string(), RowKind::Single),
+                ("4".to_string(), RowKind::Single),
+            ]
+        );
+
+        let expanded = kinds(&torrents, &HashSet::from([key.clone()]));
+        assert_eq!(
+            expanded,
+            [
+                (
+                    "3".to_string(),
+                    RowKind::Group {
+                        key: key.clone(),
+                        others: 1,
+                        expanded: true,
+                    }
+                ),
+                ("1".to_string(), RowKind::Member { key }),
+                ("2".to_string(), RowKind::Single),
+                ("4".to_string(), RowKind::Single),
+            ]
+        );
+    }
+}
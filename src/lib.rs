@@ -0,0 +1,48 @@
+//! scraper and client for [nyaa.si](https://nyaa.si)
+//!
+//! ```no_run
+//! use nyaa_rs::{Category, Client, Filter, Order, Sort};
+//!
+//! # async fn run() -> nyaa_rs::Result<()> {
+//! let client = Client::new();
+//! let torrents = client
+//!     .search(
+//!         "frieren",
+//!         Category::AnimeEnglishTranslated,
+//!         Filter::TrustedOnly,
+//!         Sort::Seeders,
+//!         Order::Descending,
+//!         1,
+//!     )
+//!     .await?;
+//!
+//! if let Some(first) = torrents.first() {
+//!     let details = client.details(first).await?;
+//!     println!("{} by {}", first.title, details.submitter);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! `Client::new().with_backend(Backend::Rss)` reads the rss feed instead of
+//! scraping the html listing, `with_fallback` retries with the other one
+//!
+//! `with_site(Site::sukebei())` searches sukebei instead, a [`Site`] can list
+//! several mirror urls that are tried in order when one is down
+//!
+//! the terminal ui lives behind the `tui` feature, turn off default features
+//! to use only the library
+
+pub mod client;
+pub mod error;
+pub mod model;
+pub mod release;
+pub mod site;
+
+pub use client::{Backend, Client};
+pub use error::{Error, ParseError, Result};
+pub use model::{
+    Category, Comment, FileEntry, Filter, Order, Sort, Torrent, TorrentDetails, TorrentStatus,
+};
+pub use release::{Episode, Release};
+pub use site::{Site, SiteKind};
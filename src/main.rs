@@ -1,6 +1,7 @@
 use std::io;
 
 use anyhow::Result;
+use clap::Parser;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
@@ -9,15 +10,24 @@ use crossterm::{
 use ratatui::{Terminal, backend::CrosstermBackend};
 
 mod app;
-mod client;
-mod model;
+mod cli;
+mod clipboard;
+mod config;
+mod filter;
+mod group;
+mod sink;
 mod theme;
 mod tui;
 
 use crate::app::App;
+use crate::cli::Cli;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    if let Some(command) = Cli::parse().command {
+        return cli::run(command).await;
+    }
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
@@ -60,13 +70,16 @@ where
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| std::time::Duration::from_secs(0));
 
-        #[allow(clippy::collapsible_if)]
         if event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                app.handle_key_event(key).await;
+            match event::read()? {
+                Event::Key(key) => app.handle_key_event(key),
+                Event::Mouse(mouse) => app.handle_mouse_event(mouse),
+                _ => {}
             }
         }
 
+        app.poll_events();
+
         if last_tick.elapsed() >= tick_rate {
             app.on_tick();
             last_tick = std::time::Instant::now();
@@ -1,48 +1,391 @@
+//! the data types returned by [`Client`](crate::Client) and the parameters it takes
+
 use std::fmt::Display;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+use crate::error::ParseError;
+use crate::release::Release;
+
+/// one row of a search listing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Torrent {
     pub title: String,
+    /// the `/view/<id>` page
     pub link: String,
+    /// the `/download/<id>.torrent` file
+    pub torrent_url: String,
+    /// empty when the listing had no magnet link
     pub magnet_url: String,
-    pub date: String,
+    /// upload time
+    pub date: DateTime<Utc>,
     pub seeders: u32,
     pub leechers: u32,
     pub downloads: u32,
+    /// as shown on the site, e.g. `1.4 GiB`
     pub size: String,
+    /// `size` in bytes, 0 when it isn't in a unit nyaa uses
+    pub size_bytes: u64,
+    /// comment count shown in the listing
+    pub comments: u32,
+    pub status: TorrentStatus,
+}
+
+impl Torrent {
+    /// the btih hash from the magnet link, lowercased
+    pub fn info_hash(&self) -> Option<String> {
+        let start = self.magnet_url.find("urn:btih:")? + "urn:btih:".len();
+        let hash = self.magnet_url[start..]
+            .split('&')
+            .next()
+            .filter(|h| !h.is_empty())?;
+        Some(hash.to_lowercase())
+    }
+
+    /// the group, episode, resolution and so on guessed from the title
+    pub fn release(&self) -> Release {
+        Release::parse(&self.title)
+    }
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+/// what the `/view/<id>` page adds on top of a listing row
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TorrentDetails {
+    /// `Anonymous` when the uploader is hidden
+    pub submitter: String,
+    /// the uploader's information link, if any
+    pub information: Option<String>,
+    pub info_hash: String,
+    /// full upload time as shown on the page, e.g. `2024-01-01 12:00 UTC`
+    pub timestamp: String,
+    /// raw markdown
+    pub description: String,
+    pub files: Vec<FileEntry>,
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comment {
+    pub author: String,
+    pub timestamp: String,
+    /// raw markdown
+    pub body: String,
+    /// the author is a trusted uploader
+    pub trusted: bool,
+    /// the author uploaded this torrent
+    pub uploader: bool,
+}
+
+/// a node of the file list, folders have no size and may have children
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub name: String,
+    pub size: Option<String>,
+    pub children: Vec<FileEntry>,
+}
+
+impl FileEntry {
+    pub fn is_folder(&self) -> bool {
+        self.size.is_none()
+    }
+}
+
+/// the row color nyaa gives an upload. there is no batch class in the
+/// listing, batches are told apart by their title, see `Release::batch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TorrentStatus {
+    #[default]
+    Normal,
+    Trusted,
+    Remake,
+}
+
+impl TorrentStatus {
+    /// maps the css class nyaa puts on each listing row
+    pub fn from_class(class: &str) -> Self {
+        match class {
+            "success" => Self::Trusted,
+            "danger" => Self::Remake,
+            _ => Self::Normal,
+        }
+    }
+}
+
+/// a nyaa.si or sukebei category, serialized as its `X_Y` code. the
+/// sukebei codes overlap with the nyaa ones, so those are written as
+/// `sukebei:X_Y`
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub enum Category {
+    #[default]
     All,
     Anime,
     AnimeMusicVideo,
     AnimeEnglishTranslated,
     AnimeNonEnglishTranslated,
     AnimeRaw,
+    Audio,
+    AudioLossless,
+    AudioLossy,
+    Literature,
+    LiteratureEnglishTranslated,
+    LiteratureNonEnglishTranslated,
+    LiteratureRaw,
+    LiveAction,
+    LiveActionEnglishTranslated,
+    LiveActionIdolPromotionalVideo,
+    LiveActionNonEnglishTranslated,
+    LiveActionRaw,
+    Pictures,
+    PicturesGraphics,
+    PicturesPhotos,
+    Software,
+    SoftwareApplications,
+    SoftwareGames,
+    Art,
+    ArtAnime,
+    ArtDoujinshi,
+    ArtGames,
+    ArtManga,
+    ArtPictures,
+    RealLife,
+    RealLifePictures,
+    RealLifeVideos,
+}
+
+impl Category {
+    pub const ALL: [Category; 24] = [
+        Self::All,
+        Self::Anime,
+        Self::AnimeMusicVideo,
+        Self::AnimeEnglishTranslated,
+        Self::AnimeNonEnglishTranslated,
+        Self::AnimeRaw,
+        Self::Audio,
+        Self::AudioLossless,
+        Self::AudioLossy,
+        Self::Literature,
+        Self::LiteratureEnglishTranslated,
+        Self::LiteratureNonEnglishTranslated,
+        Self::LiteratureRaw,
+        Self::LiveAction,
+        Self::LiveActionEnglishTranslated,
+        Self::LiveActionIdolPromotionalVideo,
+        Self::LiveActionNonEnglishTranslated,
+        Self::LiveActionRaw,
+        Self::Pictures,
+        Self::PicturesGraphics,
+        Self::PicturesPhotos,
+        Self::Software,
+        Self::SoftwareApplications,
+        Self::SoftwareGames,
+    ];
+
+    /// the sukebei tree, its codes overlap with the nyaa ones
+    pub const SUKEBEI: [Category; 10] = [
+        Self::All,
+        Self::Art,
+        Self::ArtAnime,
+        Self::ArtDoujinshi,
+        Self::ArtGames,
+        Self::ArtManga,
+        Self::ArtPictures,
+        Self::RealLife,
+        Self::RealLifePictures,
+        Self::RealLifeVideos,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::All => "0_0",
+            Self::Anime => "1_0",
+            Self::AnimeMusicVideo => "1_1",
+            Self::AnimeEnglishTranslated => "1_2",
+            Self::AnimeNonEnglishTranslated => "1_3",
+            Self::AnimeRaw => "1_4",
+            Self::Audio => "2_0",
+            Self::AudioLossless => "2_1",
+            Self::AudioLossy => "2_2",
+            Self::Literature => "3_0",
+            Self::LiteratureEnglishTranslated => "3_1",
+            Self::LiteratureNonEnglishTranslated => "3_2",
+            Self::LiteratureRaw => "3_3",
+            Self::LiveAction => "4_0",
+            Self::LiveActionEnglishTranslated => "4_1",
+            Self::LiveActionIdolPromotionalVideo => "4_2",
+            Self::LiveActionNonEnglishTranslated => "4_3",
+            Self::LiveActionRaw => "4_4",
+            Self::Pictures => "5_0",
+            Self::PicturesGraphics => "5_1",
+            Self::PicturesPhotos => "5_2",
+            Self::Software => "6_0",
+            Self::SoftwareApplications => "6_1",
+            Self::SoftwareGames => "6_2",
+            Self::Art => "1_0",
+            Self::ArtAnime => "1_1",
+            Self::ArtDoujinshi => "1_2",
+            Self::ArtGames => "1_3",
+            Self::ArtManga => "1_4",
+            Self::ArtPictures => "1_5",
+            Self::RealLife => "2_0",
+            Self::RealLifePictures => "2_1",
+            Self::RealLifeVideos => "2_2",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::All => "all categories",
+            Self::Anime => "anime",
+            Self::AnimeMusicVideo => "anime music video",
+            Self::AnimeEnglishTranslated => "english-translated",
+            Self::AnimeNonEnglishTranslated => "non-english-translated",
+            Self::AnimeRaw => "raw",
+            Self::Audio => "audio",
+            Self::AudioLossless => "lossless",
+            Self::AudioLossy => "lossy",
+            Self::Literature => "literature",
+            Self::LiteratureEnglishTranslated => "english-translated",
+            Self::LiteratureNonEnglishTranslated => "non-english-translated",
+            Self::LiteratureRaw => "raw",
+            Self::LiveAction => "live action",
+            Self::LiveActionEnglishTranslated => "english-translated",
+            Self::LiveActionIdolPromotionalVideo => "idol/promotional video",
+            Self::LiveActionNonEnglishTranslated => "non-english-translated",
+            Self::LiveActionRaw => "raw",
+            Self::Pictures => "pictures",
+            Self::PicturesGraphics => "graphics",
+            Self::PicturesPhotos => "photos",
+            Self::Software => "software",
+            Self::SoftwareApplications => "applications",
+            Self::SoftwareGames => "games",
+            Self::Art => "art",
+            Self::ArtAnime => "anime",
+            Self::ArtDoujinshi => "doujinshi",
+            Self::ArtGames => "games",
+            Self::ArtManga => "manga",
+            Self::ArtPictures => "pictures",
+            Self::RealLife => "real life",
+            Self::RealLifePictures => "photobooks and pictures",
+            Self::RealLifeVideos => "videos",
+        }
+    }
+
+    /// the top level category this one belongs to, or itself if it already is one
+    pub fn parent(&self) -> Self {
+        let main = self.code().split('_').next();
+        self.tree()
+            .iter()
+            .copied()
+            .find(|c| !c.is_subcategory() && c.code().split('_').next() == main)
+            .unwrap_or(Self::All)
+    }
+
+    fn tree(&self) -> &'static [Category] {
+        if self.is_sukebei() {
+            &Self::SUKEBEI
+        } else {
+            &Self::ALL
+        }
+    }
+
+    /// only on sukebei, `All` is on both
+    fn is_sukebei(&self) -> bool {
+        *self != Self::All && Self::SUKEBEI.contains(self)
+    }
+
+    pub fn is_subcategory(&self) -> bool {
+        !self.code().ends_with("_0")
+    }
+
+    /// `anime - raw` style label for subcategories, plain name otherwise
+    pub fn label(&self) -> String {
+        if self.is_subcategory() {
+            format!("{} - {}", self.parent().name(), self.name())
+        } else {
+            self.name().to_string()
+        }
+    }
 }
 
 impl Display for Category {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::All => write!(f, "0_0"),
-            Self::Anime => write!(f, "1_0"),
-            Self::AnimeMusicVideo => write!(f, "1_1"),
-            Self::AnimeEnglishTranslated => write!(f, "1_2"),
-            Self::AnimeNonEnglishTranslated => write!(f, "1_3"),
-            Self::AnimeRaw => write!(f, "1_4"),
+        write!(f, "{}", self.code())
+    }
+}
+
+impl From<Category> for String {
+    fn from(category: Category) -> Self {
+        if category.is_sukebei() {
+            format!("{SUKEBEI_PREFIX}{}", category.code())
+        } else {
+            category.code().to_string()
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+const SUKEBEI_PREFIX: &str = "sukebei:";
+
+impl TryFrom<String> for Category {
+    type Error = ParseError;
+
+    fn try_from(code: String) -> Result<Self, Self::Error> {
+        code.parse()
+    }
+}
+
+/// plain codes are looked up in the nyaa tree and `sukebei:X_Y` ones in the
+/// sukebei tree, the way categories are serialized. see
+/// [`SiteKind::category`](crate::site::SiteKind::category) to look up a plain
+/// code on a given site
+impl FromStr for Category {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (tree, code) = match s.strip_prefix(SUKEBEI_PREFIX) {
+            Some(code) => (&Self::SUKEBEI[..], code),
+            None => (&Self::ALL[..], s),
+        };
+        tree.iter()
+            .copied()
+            .find(|c| c.code() == code)
+            .ok_or_else(|| ParseError::new("category", s))
+    }
+}
+
+/// the column nyaa.si sorts by, `Date` is the upload id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Sort {
     #[default]
     Date,
     Downloads,
     Seeders,
+    Leechers,
     Size,
+    Comments,
+    Name,
+}
+
+impl Sort {
+    pub const ALL: [Sort; 7] = [
+        Self::Date,
+        Self::Downloads,
+        Self::Seeders,
+        Self::Leechers,
+        Self::Size,
+        Self::Comments,
+        Self::Name,
+    ];
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&s| s == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
 }
 
 impl Display for Sort {
@@ -51,7 +394,146 @@ impl Display for Sort {
             Self::Date => write!(f, "id"),
             Self::Downloads => write!(f, "downloads"),
             Self::Seeders => write!(f, "seeders"),
+            Self::Leechers => write!(f, "leechers"),
             Self::Size => write!(f, "size"),
+            Self::Comments => write!(f, "comments"),
+            Self::Name => write!(f, "name"),
         }
     }
 }
+
+impl FromStr for Sort {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "date" | "id" => Ok(Self::Date),
+            "downloads" => Ok(Self::Downloads),
+            "seeders" => Ok(Self::Seeders),
+            "leechers" => Ok(Self::Leechers),
+            "size" => Ok(Self::Size),
+            "comments" => Ok(Self::Comments),
+            "name" => Ok(Self::Name),
+            other => Err(ParseError::new("sort", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    Ascending,
+    #[default]
+    Descending,
+}
+
+impl Order {
+    pub fn flip(self) -> Self {
+        match self {
+            Self::Ascending => Self::Descending,
+            Self::Descending => Self::Ascending,
+        }
+    }
+}
+
+impl Display for Order {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ascending => write!(f, "asc"),
+            Self::Descending => write!(f, "desc"),
+        }
+    }
+}
+
+impl FromStr for Order {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "asc" | "ascending" => Ok(Self::Ascending),
+            "desc" | "descending" => Ok(Self::Descending),
+            other => Err(ParseError::new("order", other)),
+        }
+    }
+}
+
+/// the `f=` search filter
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Filter {
+    #[default]
+    NoFilter,
+    NoRemakes,
+    TrustedOnly,
+}
+
+impl Filter {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::NoFilter => "no filter",
+            Self::NoRemakes => "no remakes",
+            Self::TrustedOnly => "trusted only",
+        }
+    }
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoFilter => write!(f, "0"),
+            Self::NoRemakes => write!(f, "1"),
+            Self::TrustedOnly => write!(f, "2"),
+        }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "0" | "none" | "no-filter" => Ok(Self::NoFilter),
+            "1" | "no-remakes" => Ok(Self::NoRemakes),
+            "2" | "trusted" | "trusted-only" => Ok(Self::TrustedOnly),
+            other => Err(ParseError::new("filter", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories_round_trip_through_serde() {
+        for category in Category::ALL.into_iter().chain(Category::SUKEBEI) {
+            let json = serde_json::to_string(&category).unwrap();
+            let back: Category = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, category, "{json}");
+        }
+        assert_eq!(
+            serde_json::to_string(&Category::ArtAnime).unwrap(),
+            r#""sukebei:1_1""#
+        );
+        assert_eq!(
+            serde_json::to_string(&Category::AnimeMusicVideo).unwrap(),
+            r#""1_1""#
+        );
+    }
+
+    #[test]
+    fn plain_codes_parse_in_the_nyaa_tree() {
+        assert_eq!(
+            "1_1".parse::<Category>().unwrap(),
+            Category::AnimeMusicVideo
+        );
+        assert_eq!(
+            "sukebei:1_1".parse::<Category>().unwrap(),
+            Category::ArtAnime
+        );
+        assert_eq!("sukebei:0_0".parse::<Category>().unwrap(), Category::All);
+        assert!("1_5".parse::<Category>().is_err());
+        assert!("sukebei:6_1".parse::<Category>().is_err());
+    }
+}
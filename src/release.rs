@@ -0,0 +1,499 @@
+//! best-effort parsing of release names like
+//! `[SubsPlease] Show - 12v2 (1080p) [ABCD1234].mkv`
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ParseError;
+
+static BRACKETS: OnceLock<Regex> = OnceLock::new();
+static EXTENSION: OnceLock<Regex> = OnceLock::new();
+static RESOLUTION: OnceLock<Regex> = OnceLock::new();
+static CRC32: OnceLock<Regex> = OnceLock::new();
+static VIDEO_CODEC: OnceLock<Regex> = OnceLock::new();
+static AUDIO_CODEC: OnceLock<Regex> = OnceLock::new();
+static SOURCE: OnceLock<Regex> = OnceLock::new();
+static SEASON_EPISODE: OnceLock<Regex> = OnceLock::new();
+static DASH_EPISODE: OnceLock<Regex> = OnceLock::new();
+static SEASON: OnceLock<Regex> = OnceLock::new();
+static EPISODE: OnceLock<Regex> = OnceLock::new();
+static RANGE_TAG: OnceLock<Regex> = OnceLock::new();
+static VERSION: OnceLock<Regex> = OnceLock::new();
+static BATCH: OnceLock<Regex> = OnceLock::new();
+static SCENE_GROUP: OnceLock<Regex> = OnceLock::new();
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).unwrap())
+}
+
+/// one episode or an inclusive range, serialized as `12` or `1-12`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Episode {
+    Single(u32),
+    Range(u32, u32),
+}
+
+impl Episode {
+    pub fn contains(&self, episode: u32) -> bool {
+        match *self {
+            Self::Single(n) => n == episode,
+            Self::Range(start, end) => (start..=end).contains(&episode),
+        }
+    }
+
+    /// the episode a release is ordered by, the last one for ranges
+    pub fn last(&self) -> u32 {
+        match *self {
+            Self::Single(n) | Self::Range(_, n) => n,
+        }
+    }
+}
+
+impl fmt::Display for Episode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Single(n) => write!(f, "{n:02}"),
+            Self::Range(start, end) => write!(f, "{start:02}-{end:02}"),
+        }
+    }
+}
+
+impl From<Episode> for String {
+    fn from(episode: Episode) -> Self {
+        episode.to_string()
+    }
+}
+
+impl TryFrom<String> for Episode {
+    type Error = ParseError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let number = |n: &str| n.trim().parse().map_err(|_| ParseError::new("episode", &s));
+        match s.split_once('-') {
+            Some((start, end)) => Ok(Self::Range(number(start)?, number(end)?)),
+            None => Ok(Self::Single(number(&s)?)),
+        }
+    }
+}
+
+/// what a title says about a release, every field is a guess and may be missing
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Release {
+    pub group: Option<String>,
+    /// the series name with the group, tags and episode stripped
+    pub series: String,
+    pub season: Option<u32>,
+    pub episode: Option<Episode>,
+    /// a range of episodes, a whole season or marked as batch/complete
+    pub batch: bool,
+    /// vertical resolution, `1080` for 1080p
+    pub resolution: Option<u32>,
+    /// `H.264`, `HEVC`, `AV1` or `VP9`
+    pub video_codec: Option<String>,
+    /// `AAC`, `FLAC`, `Opus`, `AC3`, `E-AC-3`, `DTS`, `TrueHD` or `MP3`
+    pub audio_codec: Option<String>,
+    /// `WEB-DL`, `WEBRip`, `BD`, `DVD` or `TV`
+    pub source: Option<String>,
+    /// uppercase hex
+    pub crc32: Option<String>,
+    /// `2` for a v2 re-release
+    pub version: Option<u32>,
+}
+
+impl Release {
+    pub fn parse(title: &str) -> Self {
+        let title = regex(&EXTENSION, r"(?i)\.(mkv|mp4|avi|webm|m2ts|ts)$").replace(title, "");
+        let mut release = Self::default();
+
+        // tags are whatever sits in brackets, the rest is the name
+        let brackets = regex(&BRACKETS, r"\[([^\]]*)\]|\(([^)]*)\)|【([^】]*)】");
+        let mut tags = Vec::new();
+        let mut name = String::new();
+        let mut last = 0;
+        for caps in brackets.captures_iter(&title) {
+            let whole = caps.get(0).unwrap();
+            let tag = (1..=3)
+                .find_map(|i| caps.get(i))
+                .map_or("", |m| m.as_str().trim());
+            if whole.start() == 0 && release.group.is_none() && !tag.is_empty() {
+                release.group = Some(tag.to_string());
+            } else {
+                tags.push(tag);
+            }
+            name.push_str(&title[last..whole.start()]);
+            name.push(' ');
+            last = whole.end();
+        }
+        name.push_str(&title[last..]);
+
+        // scene style names use dots for spaces
+        let mut name = name.trim().to_string();
+        if !name.contains(' ') && name.matches('.').count() > 1 {
+            name = name.replace(['.', '_'], " ");
+        }
+
+        release.resolution = regex(
+            &RESOLUTION,
+            r"(?i)\b(?:(\d{3,4})[pi]|\d{3,4}x(\d{3,4})|(4k|uhd))\b",
+        )
+        .captures(&title)
+        .and_then(|caps| match caps.get(3) {
+            Some(_) => Some(2160),
+            None => caps.get(1).or(caps.get(2))?.as_str().parse().ok(),
+        });
+        release.video_codec = regex(
+            &VIDEO_CODEC,
+            r"(?i)\b(x\.?264|h\.?264|avc|x\.?265|h\.?265|hevc|av1|vp9)\b",
+        )
+        .find(&title)
+        .map(|m| normalize_video(m.as_str()));
+        release.audio_codec = regex(
+            &AUDIO_CODEC,
+            r"(?i)\b(aac|flac|opus|e-?ac-?3|ddp|ac-?3|dts|truehd|mp3)(?:\d|\b)",
+        )
+        .captures(&title)
+        .map(|caps| normalize_audio(&caps[1]));
+        release.source = regex(
+            &SOURCE,
+            r"(?i)\b(web-?dl|web-?rip|web|blu-?ray|bd-?rip|bdmv|bd|dvd-?rip|dvd|hdtv|tv-?rip)\b",
+        )
+        .find(&title)
+        .map(|m| normalize_source(m.as_str()));
+        release.crc32 = tags
+            .iter()
+            .rev()
+            .find(|tag| regex(&CRC32, r"^[0-9A-Fa-f]{8}$").is_match(tag))
+            .map(|tag| tag.to_uppercase());
+
+        // the series name ends where the first episode, season or tech token starts
+        let mut cut = name.len();
+        let mut cut_at = |start: usize| cut = cut.min(start);
+
+        if let Some(caps) = regex(
+            &SEASON_EPISODE,
+            r"(?i)\bS(\d{1,2})\s?E(\d{1,4})(?:\s?-\s?E?(\d{1,4}))?(?:v(\d))?",
+        )
+        .captures(&name)
+        {
+            cut_at(caps.get(0).unwrap().start());
+            release.season = number(caps.get(1));
+            release.episode = episode(number(caps.get(2)), number(caps.get(3)));
+            release.version = number(caps.get(4));
+        } else if let Some(caps) = regex(
+            &DASH_EPISODE,
+            r"\s-\s(?:#|EP?)?(\d{1,4})(?:\s?[-~]\s?(\d{1,4}))?(?:v(\d))?(?:\s|$)",
+        )
+        .captures(&name)
+        {
+            cut_at(caps.get(0).unwrap().start());
+            release.episode = episode(number(caps.get(1)), number(caps.get(2)));
+            release.version = number(caps.get(3));
+        } else if let Some(caps) =
+            regex(&EPISODE, r"(?i)\b(?:EP?|Episode\s?)(\d{1,4})(?:v(\d))?\b").captures(&name)
+        {
+            cut_at(caps.get(0).unwrap().start());
+            release.episode = episode(number(caps.get(1)), None);
+            release.version = number(caps.get(2));
+        }
+
+        let season = regex(
+            &SEASON,
+            r"(?i)\bS(\d{1,2})\b|\bSeason\s?(\d{1,2})\b|\b(\d{1,2})(?:st|nd|rd|th) Season\b",
+        );
+        let season_number =
+            |caps: regex::Captures| number(caps.get(1).or(caps.get(2)).or(caps.get(3)));
+        if let Some(caps) = season.captures(&name) {
+            cut_at(caps.get(0).unwrap().start());
+            release.season = release.season.or_else(|| season_number(caps));
+        }
+        // `Show (Season 1) [1080p] (Batch)` keeps it in a tag
+        release.season = release.season.or_else(|| {
+            tags.iter()
+                .find_map(|tag| season_number(season.captures(tag)?))
+        });
+
+        if release.episode.is_none() {
+            release.episode = tags.iter().find_map(|tag| {
+                let caps = regex(&RANGE_TAG, r"^(\d{1,4})\s?[-~]\s?(\d{1,4})$").captures(tag)?;
+                episode(number(caps.get(1)), number(caps.get(2)))
+            });
+        }
+
+        for tech in [&RESOLUTION, &VIDEO_CODEC, &AUDIO_CODEC, &SOURCE] {
+            if let Some(m) = tech.get().and_then(|re| re.find(&name)) {
+                cut_at(m.start());
+            }
+        }
+        let batch = regex(&BATCH, r"(?i)\b(batch|complete)\b");
+        if let Some(m) = batch.find(&name) {
+            cut_at(m.start());
+        }
+
+        release.version = release.version.or_else(|| {
+            number(
+                regex(&VERSION, r"(?i)\bv(\d)\b")
+                    .captures(&title)
+                    .and_then(|caps| caps.get(1)),
+            )
+        });
+        release.batch = matches!(release.episode, Some(Episode::Range(..)))
+            || (release.season.is_some() && release.episode.is_none())
+            || batch.is_match(&title);
+
+        // `Show.S01E02.1080p.WEB-DL.x264-GROUP`
+        if release.group.is_none()
+            && let Some(caps) = regex(&SCENE_GROUP, r"-([A-Za-z0-9]+)$").captures(&name)
+        {
+            let group = caps.get(1).unwrap().as_str();
+            let is_tech = [&VIDEO_CODEC, &AUDIO_CODEC, &SOURCE]
+                .iter()
+                .any(|re| re.get().is_some_and(|re| re.is_match(group)));
+            if !is_tech && cut < name.len() {
+                release.group = Some(group.to_string());
+            }
+        }
+
+        let series = name[..cut].trim_matches(|c: char| c.is_whitespace() || "-_.~|".contains(c));
+        release.series = if series.is_empty() {
+            name.trim().to_string()
+        } else {
+            series.split_whitespace().collect::<Vec<_>>().join(" ")
+        };
+        release
+    }
+
+    /// `S2E12`, `12`, `01-12`, or `batch` for a batch without episode numbers
+    pub fn episode_label(&self) -> String {
+        match (self.season, self.episode) {
+            (Some(season), Some(episode)) => format!("S{season}E{episode}"),
+            (None, Some(episode)) => episode.to_string(),
+            (Some(season), None) => format!("S{season}"),
+            (None, None) if self.batch => "batch".to_string(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+fn number(m: Option<regex::Match>) -> Option<u32> {
+    m?.as_str().parse().ok()
+}
+
+fn episode(start: Option<u32>, end: Option<u32>) -> Option<Episode> {
+    match (start?, end) {
+        (start, Some(end)) if end > start => Some(Episode::Range(start, end)),
+        (start, _) => Some(Episode::Single(start)),
+    }
+}
+
+fn normalize_video(codec: &str) -> String {
+    let codec = codec.to_lowercase().replace('.', "");
+    match codec.as_str() {
+        "x264" | "h264" | "avc" => "H.264",
+        "x265" | "h265" | "hevc" => "HEVC",
+        "av1" => "AV1",
+        _ => "VP9",
+    }
+    .to_string()
+}
+
+fn normalize_audio(codec: &str) -> String {
+    let codec = codec.to_lowercase().replace('-', "");
+    match codec.as_str() {
+        "aac" => "AAC",
+        "flac" => "FLAC",
+        "opus" => "Opus",
+        "eac3" | "ddp" => "E-AC-3",
+        "ac3" => "AC3",
+        "dts" => "DTS",
+        "truehd" => "TrueHD",
+        _ => "MP3",
+    }
+    .to_string()
+}
+
+fn normalize_source(source: &str) -> String {
+    let source = source.to_lowercase().replace('-', "");
+    match source.as_str() {
+        "webdl" | "web" => "WEB-DL",
+        "webrip" => "WEBRip",
+        "bluray" | "bdrip" | "bdmv" | "bd" => "BD",
+        "dvdrip" | "dvd" => "DVD",
+        _ => "TV",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Case {
+        title: &'static str,
+        group: Option<&'static str>,
+        series: &'static str,
+        season: Option<u32>,
+        episode: Option<Episode>,
+        batch: bool,
+        resolution: Option<u32>,
+    }
+
+    const CASES: &[Case] = &[
+        Case {
+            title: "[SubsPlease] Sousou no Frieren - 12v2 (1080p) [ABCD1234].mkv",
+            group: Some("SubsPlease"),
+            series: "Sousou no Frieren",
+            season: None,
+            episode: Some(Episode::Single(12)),
+            batch: false,
+            resolution: Some(1080),
+        },
+        Case {
+            title: "Show.Name.S01E02.1080p.WEB-DL.x264-GROUP",
+            group: Some("GROUP"),
+            series: "Show Name",
+            season: Some(1),
+            episode: Some(Episode::Single(2)),
+            batch: false,
+            resolution: Some(1080),
+        },
+        Case {
+            title: "Show Name S02 1080p BluRay x265 10bit FLAC-Group",
+            group: Some("Group"),
+            series: "Show Name",
+            season: Some(2),
+            episode: None,
+            batch: true,
+            resolution: Some(1080),
+        },
+        Case {
+            title: "[Erai-raws] Show - 01 ~ 12 [1080p][Multiple Subtitle]",
+            group: Some("Erai-raws"),
+            series: "Show",
+            season: None,
+            episode: Some(Episode::Range(1, 12)),
+            batch: true,
+            resolution: Some(1080),
+        },
+        Case {
+            title: "[Group] Show (01-12) [BD 720p]",
+            group: Some("Group"),
+            series: "Show",
+            season: None,
+            episode: Some(Episode::Range(1, 12)),
+            batch: true,
+            resolution: Some(720),
+        },
+        Case {
+            title: "[SubsPlease] Show 2nd Season - 05 (720p) [12345678].mkv",
+            group: Some("SubsPlease"),
+            series: "Show",
+            season: Some(2),
+            episode: Some(Episode::Single(5)),
+            batch: false,
+            resolution: Some(720),
+        },
+        Case {
+            title: "[Judas] Show (Season 1) [BD 1080p][HEVC x265 10bit][Dual-Audio][Eng-Subs] (Batch)",
+            group: Some("Judas"),
+            series: "Show",
+            season: Some(1),
+            episode: None,
+            batch: true,
+            resolution: Some(1080),
+        },
+        Case {
+            title: "[Group] Show Complete [1080p]",
+            group: Some("Group"),
+            series: "Show",
+            season: None,
+            episode: None,
+            batch: true,
+            resolution: Some(1080),
+        },
+        Case {
+            title: "[Group] Show the Movie [4K]",
+            group: Some("Group"),
+            series: "Show the Movie",
+            season: None,
+            episode: None,
+            batch: false,
+            resolution: Some(2160),
+        },
+    ];
+
+    #[test]
+    fn parses_common_release_names() {
+        for case in CASES {
+            let release = Release::parse(case.title);
+            let got = (
+                release.group.as_deref(),
+                release.series.as_str(),
+                release.season,
+                release.episode,
+                release.batch,
+                release.resolution,
+            );
+            let expected = (
+                case.group,
+                case.series,
+                case.season,
+                case.episode,
+                case.batch,
+                case.resolution,
+            );
+            assert_eq!(got, expected, "{}", case.title);
+        }
+    }
+
+    #[test]
+    fn parses_tech_tags() {
+        let release =
+            Release::parse("[SubsPlease] Sousou no Frieren - 12v2 (1080p) [ABCD1234].mkv");
+        assert_eq!(release.version, Some(2));
+        assert_eq!(release.crc32.as_deref(), Some("ABCD1234"));
+
+        let release = Release::parse("Show.Name.S01E02.1080p.WEB-DL.DDP5.1.H.264-GROUP");
+        assert_eq!(release.source.as_deref(), Some("WEB-DL"));
+        assert_eq!(release.audio_codec.as_deref(), Some("E-AC-3"));
+        assert_eq!(release.video_codec.as_deref(), Some("H.264"));
+
+        let release = Release::parse("[Group] Show - 03 [BDRip 1920x1080 HEVC FLAC]");
+        assert_eq!(release.source.as_deref(), Some("BD"));
+        assert_eq!(release.resolution, Some(1080));
+        assert_eq!(release.video_codec.as_deref(), Some("HEVC"));
+        assert_eq!(release.audio_codec.as_deref(), Some("FLAC"));
+        assert_eq!(release.crc32, None);
+    }
+
+    #[test]
+    fn words_that_contain_a_codec_are_not_one() {
+        let release = Release::parse("[Group] Aachen Academy - 01 [720p]");
+        assert_eq!(release.audio_codec, None);
+        assert_eq!(release.series, "Aachen Academy");
+    }
+
+    #[test]
+    fn labels_episodes() {
+        let label = |title| Release::parse(title).episode_label();
+        assert_eq!(label("Show.S02E12.1080p-GRP"), "S2E12");
+        assert_eq!(label("[G] Show - 07 [1080p]"), "07");
+        assert_eq!(label("[G] Show - 01 ~ 12 [1080p]"), "01-12");
+        assert_eq!(label("[G] Show (Season 3) (Batch)"), "S3");
+        assert_eq!(label("[G] Show Complete [1080p]"), "batch");
+        assert_eq!(label("[G] Show the Movie [1080p]"), "");
+    }
+
+    #[test]
+    fn episodes_round_trip_through_strings() {
+        for episode in [Episode::Single(7), Episode::Range(1, 12)] {
+            assert_eq!(Episode::try_from(episode.to_string()), Ok(episode));
+        }
+        assert!(Episode::try_from("seven".to_string()).is_err());
+        assert!(Episode::Range(1, 12).contains(12));
+        assert!(!Episode::Single(1).contains(2));
+    }
+}
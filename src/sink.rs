@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use reqwest::Client as HttpClient;
+use serde::Deserialize;
+
+use nyaa_rs::model::Torrent;
+
+mod aria2;
+mod command;
+mod deluge;
+mod qbittorrent;
+mod transmission;
+
+pub use aria2::{Aria2, Aria2Config};
+pub use command::{Command, CommandConfig};
+pub use deluge::{Deluge, DelugeConfig};
+pub use qbittorrent::{Qbittorrent, QbittorrentConfig};
+pub use transmission::{Transmission, TransmissionConfig};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// the client every web backend talks through, `cookies` keeps the session
+/// of the ones that log in
+fn http_client(cookies: bool) -> HttpClient {
+    HttpClient::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .cookie_store(cookies)
+        .build()
+        .unwrap_or_default()
+}
+
+/// a backend torrents can be handed to, as written in `config.json`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SinkConfig {
+    #[default]
+    Open,
+    Qbittorrent(QbittorrentConfig),
+    Transmission(TransmissionConfig),
+    Aria2(Aria2Config),
+    Deluge(DelugeConfig),
+    Command(CommandConfig),
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum Sink {
+    /// hands the magnet to the system's default handler
+    #[default]
+    Open,
+    Qbittorrent(Qbittorrent),
+    Transmission(Transmission),
+    Aria2(Aria2),
+    Deluge(Deluge),
+    Command(Command),
+}
+
+impl Sink {
+    pub fn from_config(config: &SinkConfig) -> Self {
+        match config {
+            SinkConfig::Open => Self::Open,
+            SinkConfig::Qbittorrent(c) => Self::Qbittorrent(Qbittorrent::new(c.clone())),
+            SinkConfig::Transmission(c) => Self::Transmission(Transmission::new(c.clone())),
+            SinkConfig::Aria2(c) => Self::Aria2(Aria2::new(c.clone())),
+            SinkConfig::Deluge(c) => Self::Deluge(Deluge::new(c.clone())),
+            SinkConfig::Command(c) => Self::Command(Command::new(c.clone())),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Open => "magnet handler",
+            Self::Qbittorrent(_) => "qbittorrent",
+            Self::Transmission(_) => "transmission",
+            Self::Aria2(_) => "aria2",
+            Self::Deluge(_) => "deluge",
+            Self::Command(c) => c.program().unwrap_or("command"),
+        }
+    }
+
+    /// hands the torrent over, returning any output worth showing the user
+    pub async fn send(&self, torrent: &Torrent) -> Result<Vec<String>> {
+        match self {
+            Self::Open => {
+                if torrent.magnet_url.is_empty() {
+                    bail!("no magnet link");
+                }
+                open::that(&torrent.magnet_url)?;
+            }
+            Self::Qbittorrent(q) => q.add(torrent).await?,
+            Self::Transmission(t) => t.add(torrent).await?,
+            Self::Aria2(a) => a.add(torrent).await?,
+            Self::Deluge(d) => d.add(torrent).await?,
+            Self::Command(c) => return c.run(torrent).await,
+        }
+        Ok(Vec::new())
+    }
+}
+
+/// a listing row for the sink tests
+#[cfg(test)]
+pub(crate) fn test_torrent() -> Torrent {
+    Torrent {
+        title: "[SubsPlease] Show - 01 (1080p) [ABCD1234].mkv".to_string(),
+        link: "https://nyaa.si/view/1".to_string(),
+        torrent_url: "https://nyaa.si/download/1.torrent".to_string(),
+        magnet_url: "magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567".to_string(),
+        date: chrono::DateTime::UNIX_EPOCH,
+        seeders: 10,
+        leechers: 2,
+        downloads: 100,
+        size: "1.4 GiB".to_string(),
+        size_bytes: 1_503_238_554,
+        comments: 0,
+        status: Default::default(),
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::{Context, Result, bail};
+use reqwest::Client as HttpClient;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use nyaa_rs::model::Torrent;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Aria2Config {
+    /// json-rpc endpoint, e.g. `http://localhost:6800/jsonrpc`
+    pub url: String,
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub dir: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+/// talks to aria2 over json-rpc
+#[derive(Debug, Clone)]
+pub struct Aria2 {
+    config: Aria2Config,
+    http: HttpClient,
+}
+
+impl Aria2 {
+    pub fn new(config: Aria2Config) -> Self {
+        Self {
+            config,
+            http: super::http_client(false),
+        }
+    }
+
+    pub async fn add(&self, torrent: &Torrent) -> Result<()> {
+        let uri = if torrent.magnet_url.is_empty() {
+            &torrent.torrent_url
+        } else {
+            &torrent.magnet_url
+        };
+
+        let mut params = Vec::new();
+        if let Some(secret) = &self.config.secret {
+            params.push(json!(format!("token:{secret}")));
+        }
+        params.push(json!([uri]));
+        if let Some(dir) = &self.config.dir {
+            params.push(json!({ "dir": dir }));
+        }
+
+        let response = self
+            .http
+            .post(&self.config.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": "nyaa-rs",
+                "method": "aria2.addUri",
+                "params": Value::Array(params),
+            }))
+            .send()
+            .await
+            .context("could not reach aria2")?;
+
+        // aria2 answers errors with a 400 and a json body, so read it either way
+        let status = response.status();
+        let body: RpcResponse = response
+            .json()
+            .await
+            .with_context(|| format!("aria2 returned {status}"))?;
+
+        if let Some(error) = body.error {
+            bail!("aria2 error {}: {}", error.code, error.message);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::test_torrent;
+    use mockito::{Matcher, Server};
+
+    fn aria2(server: &Server) -> Aria2 {
+        Aria2::new(Aria2Config {
+            url: format!("{}/jsonrpc", server.url()),
+            secret: Some("s3cret".to_string()),
+            dir: Some("/data".to_string()),
+        })
+    }
+
+    #[tokio::test]
+    async fn adds_the_magnet_with_the_secret_and_dir() {
+        let mut server = Server::new_async().await;
+        let add = server
+            .mock("POST", "/jsonrpc")
+            .match_body(Matcher::PartialJson(json!({
+                "method": "aria2.addUri",
+                "params": ["token:s3cret", [test_torrent().magnet_url], { "dir": "/data" }],
+            })))
+            .with_body(r#"{"jsonrpc":"2.0","id":"nyaa-rs","result":"2089b05ecca3d829"}"#)
+            .create_async()
+            .await;
+
+        aria2(&server).add(&test_torrent()).await.unwrap();
+        add.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn reports_the_rpc_error_from_a_400() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/jsonrpc")
+            .with_status(400)
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":"nyaa-rs","error":{"code":1,"message":"Unauthorized"}}"#,
+            )
+            .create_async()
+            .await;
+
+        let err = aria2(&server).add(&test_torrent()).await.unwrap_err();
+        assert_eq!(err.to_string(), "aria2 error 1: Unauthorized");
+    }
+
+    #[tokio::test]
+    async fn fails_on_a_body_that_is_not_json() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/jsonrpc")
+            .with_status(502)
+            .with_body("bad gateway")
+            .create_async()
+            .await;
+
+        let err = aria2(&server).add(&test_torrent()).await.unwrap_err();
+        assert!(err.to_string().contains("502"), "{err}");
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use tokio::process::Command as Process;
+
+use nyaa_rs::model::Torrent;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommandConfig {
+    /// program and arguments, placeholders like `{magnet}` are filled in per torrent
+    pub command: Vec<String>,
+}
+
+/// runs a user supplied program for each torrent
+#[derive(Debug, Clone)]
+pub struct Command {
+    config: CommandConfig,
+}
+
+impl Command {
+    pub fn new(config: CommandConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn program(&self) -> Option<&str> {
+        self.config.command.first().map(String::as_str)
+    }
+
+    /// runs the command and returns the non-empty lines it printed followed by
+    /// its exit status
+    pub async fn run(&self, torrent: &Torrent) -> Result<Vec<String>> {
+        let mut args = self.config.command.iter().map(|arg| fill(arg, torrent));
+        let program = args.next().context("command is empty")?;
+
+        let output = Process::new(&program)
+            .args(args)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .with_context(|| format!("could not run {program}"))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        if !output.status.success() {
+            let reason = stderr
+                .lines()
+                .chain(stdout.lines())
+                .map(str::trim)
+                .rfind(|l| !l.is_empty())
+                .unwrap_or_default();
+            bail!("{program} failed ({}): {reason}", output.status);
+        }
+
+        let mut lines: Vec<String> = stdout
+            .lines()
+            .chain(stderr.lines())
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect();
+        lines.push(output.status.to_string());
+        Ok(lines)
+    }
+}
+
+/// replaces each placeholder in one pass, so values that contain something
+/// like `{link}` are left as they are
+fn fill(template: &str, torrent: &Torrent) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let placeholder = tail
+            .find('}')
+            .and_then(|end| Some((end, placeholder(&tail[1..end], torrent)?)));
+        match placeholder {
+            Some((end, value)) => {
+                filled.push_str(&value);
+                rest = &tail[end + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = &tail[1..];
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+fn placeholder(name: &str, torrent: &Torrent) -> Option<String> {
+    let value = match name {
+        "magnet" => torrent.magnet_url.clone(),
+        "title" => torrent.title.clone(),
+        "infohash" => torrent.info_hash().unwrap_or_default(),
+        "link" => torrent.link.clone(),
+        "torrent" => torrent.torrent_url.clone(),
+        "size" => torrent.size.clone(),
+        "date" => torrent.date.format("%Y-%m-%d").to_string(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::test_torrent;
+
+    #[test]
+    fn fills_every_placeholder() {
+        let torrent = test_torrent();
+        assert_eq!(
+            fill("{link} {size} {date} {unknown} {infohash", &torrent),
+            "https://nyaa.si/view/1 1.4 GiB 1970-01-01 {unknown} {infohash"
+        );
+        assert_eq!(
+            fill("--hash={infohash}", &torrent),
+            "--hash=0123456789abcdef0123456789abcdef01234567"
+        );
+    }
+
+    #[test]
+    fn leaves_placeholders_inside_values_alone() {
+        let torrent = Torrent {
+            title: "Show {link} {magnet}".to_string(),
+            ..test_torrent()
+        };
+        assert_eq!(
+            fill("{title}|{link}", &torrent),
+            "Show {link} {magnet}|https://nyaa.si/view/1"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{Context, Result, bail};
+use reqwest::Client as HttpClient;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use nyaa_rs::model::Torrent;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DelugeConfig {
+    /// web ui address, e.g. `http://localhost:8112`
+    pub url: String,
+    pub password: String,
+    #[serde(default)]
+    pub download_location: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    #[serde(default)]
+    result: Value,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+/// talks to the deluge web ui json api, the session lives in a cookie
+#[derive(Debug, Clone)]
+pub struct Deluge {
+    config: DelugeConfig,
+    http: HttpClient,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Deluge {
+    pub fn new(config: DelugeConfig) -> Self {
+        Self {
+            config,
+            http: super::http_client(true),
+            next_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let response = self
+            .http
+            .post(format!("{}/json", self.config.url.trim_end_matches('/')))
+            .json(&json!({ "method": method, "params": params, "id": id }))
+            .send()
+            .await
+            .context("could not reach deluge")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            bail!("deluge returned {status}");
+        }
+
+        let body: RpcResponse = response.json().await?;
+        if let Some(error) = body.error {
+            bail!("deluge: {}", error.message);
+        }
+        Ok(body.result)
+    }
+
+    pub async fn add(&self, torrent: &Torrent) -> Result<()> {
+        if self
+            .call("auth.login", json!([self.config.password]))
+            .await?
+            != json!(true)
+        {
+            bail!("deluge login failed, check the password");
+        }
+
+        let mut options = json!({});
+        if let Some(location) = &self.config.download_location {
+            options["download_location"] = json!(location);
+        }
+
+        let (method, uri) = if torrent.magnet_url.is_empty() {
+            ("core.add_torrent_url", &torrent.torrent_url)
+        } else {
+            ("core.add_torrent_magnet", &torrent.magnet_url)
+        };
+
+        // deluge answers null when the torrent is already in the session
+        if self.call(method, json!([uri, options])).await?.is_null() {
+            bail!("deluge did not add the torrent, it may already exist");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::test_torrent;
+    use mockito::{Matcher, Server};
+
+    fn deluge(server: &Server) -> Deluge {
+        Deluge::new(DelugeConfig {
+            url: server.url(),
+            password: "deluge".to_string(),
+            download_location: Some("/data".to_string()),
+        })
+    }
+
+    async fn login(server: &mut Server, result: &str) -> mockito::Mock {
+        server
+            .mock("POST", "/json")
+            .match_body(Matcher::PartialJson(
+                json!({ "method": "auth.login", "params": ["deluge"] }),
+            ))
+            .with_header("set-cookie", "_session_id=abc; path=/")
+            .with_body(format!(r#"{{"result":{result},"error":null,"id":1}}"#))
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn logs_in_and_adds_the_magnet() {
+        let mut server = Server::new_async().await;
+        let login = login(&mut server, "true").await;
+        let add = server
+            .mock("POST", "/json")
+            .match_header("cookie", "_session_id=abc")
+            .match_body(Matcher::PartialJson(json!({
+                "method": "core.add_torrent_magnet",
+                "params": [test_torrent().magnet_url, { "download_location": "/data" }],
+            })))
+            .with_body(r#"{"result":"0123456789abcdef","error":null,"id":2}"#)
+            .create_async()
+            .await;
+
+        deluge(&server).add(&test_torrent()).await.unwrap();
+        login.assert_async().await;
+        add.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn fails_on_a_wrong_password() {
+        let mut server = Server::new_async().await;
+        login(&mut server, "false").await;
+
+        let err = deluge(&server).add(&test_torrent()).await.unwrap_err();
+        assert!(err.to_string().contains("check the password"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn reports_the_rpc_error() {
+        let mut server = Server::new_async().await;
+        login(&mut server, "true").await;
+        server
+            .mock("POST", "/json")
+            .match_body(Matcher::PartialJson(
+                json!({ "method": "core.add_torrent_magnet" }),
+            ))
+            .with_body(r#"{"result":null,"error":{"message":"Not authenticated","code":1},"id":2}"#)
+            .create_async()
+            .await;
+
+        let err = deluge(&server).add(&test_torrent()).await.unwrap_err();
+        assert_eq!(err.to_string(), "deluge: Not authenticated");
+    }
+
+    #[tokio::test]
+    async fn fails_when_the_torrent_already_exists() {
+        let mut server = Server::new_async().await;
+        login(&mut server, "true").await;
+        server
+            .mock("POST", "/json")
+            .match_body(Matcher::PartialJson(
+                json!({ "method": "core.add_torrent_magnet" }),
+            ))
+            .with_body(r#"{"result":null,"error":null,"id":2}"#)
+            .create_async()
+            .await;
+
+        let err = deluge(&server).add(&test_torrent()).await.unwrap_err();
+        assert!(err.to_string().contains("already exist"), "{err}");
+    }
+}
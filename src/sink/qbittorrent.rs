@@ -0,0 +1,244 @@
+use anyhow::{Context, Result, bail};
+use reqwest::{Client as HttpClient, StatusCode};
+use serde::Deserialize;
+
+use nyaa_rs::model::Torrent;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QbittorrentConfig {
+    /// web ui address, e.g. `http://localhost:8080`
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub save_path: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// talks to the qbittorrent web api (v2)
+#[derive(Debug, Clone)]
+pub struct Qbittorrent {
+    config: QbittorrentConfig,
+    http: HttpClient,
+}
+
+impl Qbittorrent {
+    pub fn new(config: QbittorrentConfig) -> Self {
+        Self {
+            config,
+            http: super::http_client(true),
+        }
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/api/v2/{path}", self.config.url.trim_end_matches('/'))
+    }
+
+    /// logs in and keeps the SID cookie, skipped when no username is set
+    /// (e.g. localhost auth bypass is enabled)
+    async fn login(&self) -> Result<()> {
+        let Some(username) = &self.config.username else {
+            return Ok(());
+        };
+        let password = self.config.password.as_deref().unwrap_or_default();
+
+        let response = self
+            .http
+            .post(self.endpoint("auth/login"))
+            .form(&[("username", username.as_str()), ("password", password)])
+            .send()
+            .await
+            .context("could not reach qbittorrent")?;
+
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() || body.trim() != "Ok." {
+            bail!("qbittorrent login failed ({status})");
+        }
+        Ok(())
+    }
+
+    pub async fn add(&self, torrent: &Torrent) -> Result<()> {
+        self.login().await?;
+
+        let url = if torrent.magnet_url.is_empty() {
+            &torrent.torrent_url
+        } else {
+            &torrent.magnet_url
+        };
+        let tags = self.config.tags.join(",");
+
+        let mut form = vec![("urls", url.as_str())];
+        if let Some(save_path) = &self.config.save_path {
+            form.push(("savepath", save_path));
+        }
+        if let Some(category) = &self.config.category {
+            form.push(("category", category));
+        }
+        if !tags.is_empty() {
+            form.push(("tags", &tags));
+        }
+
+        let response = self
+            .http
+            .post(self.endpoint("torrents/add"))
+            .form(&form)
+            .send()
+            .await
+            .context("could not reach qbittorrent")?;
+
+        match response.status() {
+            StatusCode::OK => {
+                let body = response.text().await?;
+                if body.trim() == "Fails." {
+                    bail!("qbittorrent rejected the torrent");
+                }
+                Ok(())
+            }
+            StatusCode::FORBIDDEN => bail!("qbittorrent refused the request, check credentials"),
+            status => bail!("qbittorrent returned {status}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::test_torrent;
+    use mockito::{Matcher, Server};
+
+    fn config(url: String) -> QbittorrentConfig {
+        QbittorrentConfig {
+            url,
+            username: Some("admin".to_string()),
+            password: Some("adminadmin".to_string()),
+            save_path: Some("/data/anime".to_string()),
+            category: Some("anime".to_string()),
+            tags: vec!["nyaa".to_string(), "new".to_string()],
+        }
+    }
+
+    fn form(fields: &[(&str, &str)]) -> Matcher {
+        Matcher::AllOf(
+            fields
+                .iter()
+                .map(|(k, v)| Matcher::UrlEncoded(k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn logs_in_and_adds_with_the_configured_fields() {
+        let mut server = Server::new_async().await;
+        let login = server
+            .mock("POST", "/api/v2/auth/login")
+            .match_body(form(&[("username", "admin"), ("password", "adminadmin")]))
+            .with_header("set-cookie", "SID=abc; path=/")
+            .with_body("Ok.")
+            .create_async()
+            .await;
+        let add = server
+            .mock("POST", "/api/v2/torrents/add")
+            .match_header("cookie", "SID=abc")
+            .match_body(form(&[
+                ("urls", &test_torrent().magnet_url),
+                ("savepath", "/data/anime"),
+                ("category", "anime"),
+                ("tags", "nyaa,new"),
+            ]))
+            .with_body("Ok.")
+            .create_async()
+            .await;
+
+        let qbittorrent = Qbittorrent::new(config(server.url()));
+        qbittorrent.add(&test_torrent()).await.unwrap();
+        login.assert_async().await;
+        add.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn skips_login_without_a_username() {
+        let mut server = Server::new_async().await;
+        let login = server
+            .mock("POST", "/api/v2/auth/login")
+            .expect(0)
+            .create_async()
+            .await;
+        let add = server
+            .mock("POST", "/api/v2/torrents/add")
+            .with_body("Ok.")
+            .create_async()
+            .await;
+
+        let qbittorrent = Qbittorrent::new(QbittorrentConfig {
+            username: None,
+            ..config(server.url())
+        });
+        qbittorrent.add(&test_torrent()).await.unwrap();
+        login.assert_async().await;
+        add.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn fails_on_a_rejected_login() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/api/v2/auth/login")
+            .with_body("Fails.")
+            .create_async()
+            .await;
+
+        let err = Qbittorrent::new(config(server.url()))
+            .add(&test_torrent())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("login failed"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn fails_on_forbidden() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/api/v2/auth/login")
+            .with_body("Ok.")
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/api/v2/torrents/add")
+            .with_status(403)
+            .create_async()
+            .await;
+
+        let err = Qbittorrent::new(config(server.url()))
+            .add(&test_torrent())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("check credentials"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn fails_when_the_torrent_is_rejected() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/api/v2/auth/login")
+            .with_body("Ok.")
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/api/v2/torrents/add")
+            .with_body("Fails.")
+            .create_async()
+            .await;
+
+        let err = Qbittorrent::new(config(server.url()))
+            .add(&test_torrent())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("rejected"), "{err}");
+    }
+}
@@ -0,0 +1,206 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result, bail};
+use reqwest::{Client as HttpClient, StatusCode};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use nyaa_rs::model::Torrent;
+
+const SESSION_HEADER: &str = "X-Transmission-Session-Id";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransmissionConfig {
+    /// rpc endpoint, e.g. `http://localhost:9091/transmission/rpc`
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub download_dir: Option<String>,
+    #[serde(default)]
+    pub paused: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    result: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+/// talks to transmission-daemon over its json rpc
+#[derive(Debug, Clone)]
+pub struct Transmission {
+    config: TransmissionConfig,
+    http: HttpClient,
+    session_id: Arc<Mutex<Option<String>>>,
+}
+
+impl Transmission {
+    pub fn new(config: TransmissionConfig) -> Self {
+        Self {
+            config,
+            http: super::http_client(false),
+            session_id: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// sends one rpc call, redoing it once when transmission hands out a new
+    /// session id with a 409
+    async fn call(&self, body: &Value) -> Result<RpcResponse> {
+        for _ in 0..2 {
+            let mut request = self.http.post(&self.config.url).json(body);
+            if let Some(username) = &self.config.username {
+                request = request.basic_auth(username, self.config.password.as_deref());
+            }
+            let session_id = self.session_id.lock().ok().and_then(|id| id.clone());
+            if let Some(id) = session_id {
+                request = request.header(SESSION_HEADER, id);
+            }
+
+            let response = request
+                .send()
+                .await
+                .context("could not reach transmission")?;
+
+            match response.status() {
+                StatusCode::CONFLICT => {
+                    let id = response
+                        .headers()
+                        .get(SESSION_HEADER)
+                        .and_then(|v| v.to_str().ok())
+                        .context("transmission sent no session id")?
+                        .to_string();
+                    if let Ok(mut session_id) = self.session_id.lock() {
+                        *session_id = Some(id);
+                    }
+                }
+                StatusCode::UNAUTHORIZED => {
+                    bail!("transmission refused the request, check credentials")
+                }
+                status if !status.is_success() => bail!("transmission returned {status}"),
+                _ => return Ok(response.json().await?),
+            }
+        }
+        bail!("transmission kept rejecting the session id")
+    }
+
+    pub async fn add(&self, torrent: &Torrent) -> Result<()> {
+        let filename = if torrent.magnet_url.is_empty() {
+            &torrent.torrent_url
+        } else {
+            &torrent.magnet_url
+        };
+
+        let mut arguments = json!({
+            "filename": filename,
+            "paused": self.config.paused,
+        });
+        if let Some(dir) = &self.config.download_dir {
+            arguments["download-dir"] = json!(dir);
+        }
+
+        let response = self
+            .call(&json!({ "method": "torrent-add", "arguments": arguments }))
+            .await?;
+
+        if response.result != "success" {
+            bail!("transmission: {}", response.result);
+        }
+        if response.arguments.get("torrent-duplicate").is_some() {
+            bail!("transmission already has this torrent");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sink::test_torrent;
+    use mockito::{Matcher, Server};
+
+    const RPC: &str = "/transmission/rpc";
+
+    fn transmission(server: &Server) -> Transmission {
+        Transmission::new(TransmissionConfig {
+            url: format!("{}{RPC}", server.url()),
+            username: None,
+            password: None,
+            download_dir: Some("/data".to_string()),
+            paused: true,
+        })
+    }
+
+    #[tokio::test]
+    async fn retries_with_the_session_id_from_a_409() {
+        let mut server = Server::new_async().await;
+        let conflict = server
+            .mock("POST", RPC)
+            .match_header(SESSION_HEADER, Matcher::Missing)
+            .with_status(409)
+            .with_header(SESSION_HEADER, "token")
+            .create_async()
+            .await;
+        let add = server
+            .mock("POST", RPC)
+            .match_header(SESSION_HEADER, "token")
+            .match_body(Matcher::PartialJson(json!({
+                "method": "torrent-add",
+                "arguments": {
+                    "filename": test_torrent().magnet_url,
+                    "download-dir": "/data",
+                    "paused": true,
+                },
+            })))
+            .with_body(r#"{"result":"success","arguments":{"torrent-added":{}}}"#)
+            .create_async()
+            .await;
+
+        let transmission = transmission(&server);
+        transmission.add(&test_torrent()).await.unwrap();
+        conflict.assert_async().await;
+        add.assert_async().await;
+
+        // the id is kept, so the next call goes straight through
+        transmission.add(&test_torrent()).await.unwrap();
+        conflict.expect(1).assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn gives_up_when_the_session_id_keeps_being_rejected() {
+        let mut server = Server::new_async().await;
+        let conflict = server
+            .mock("POST", RPC)
+            .with_status(409)
+            .with_header(SESSION_HEADER, "token")
+            .expect(2)
+            .create_async()
+            .await;
+
+        let err = transmission(&server)
+            .add(&test_torrent())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("session id"), "{err}");
+        conflict.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn fails_on_a_duplicate() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", RPC)
+            .with_body(r#"{"result":"success","arguments":{"torrent-duplicate":{}}}"#)
+            .create_async()
+            .await;
+
+        let err = transmission(&server)
+            .add(&test_torrent())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already has"), "{err}");
+    }
+}
@@ -0,0 +1,70 @@
+//! the nyaav2 instances a [`Client`](crate::Client) can talk to
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::Category;
+
+/// which category tree an instance uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SiteKind {
+    #[default]
+    Nyaa,
+    Sukebei,
+}
+
+impl SiteKind {
+    pub fn categories(self) -> &'static [Category] {
+        match self {
+            Self::Nyaa => &Category::ALL,
+            Self::Sukebei => &Category::SUKEBEI,
+        }
+    }
+
+    /// looks up an `X_Y` code in this tree
+    pub fn category(self, code: &str) -> Option<Category> {
+        let code = code.trim();
+        self.categories().iter().copied().find(|c| c.code() == code)
+    }
+}
+
+/// a site and its mirrors, tried in order when one times out or errors with 5xx
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Site {
+    pub name: String,
+    pub urls: Vec<String>,
+    #[serde(default)]
+    pub kind: SiteKind,
+}
+
+impl Default for Site {
+    fn default() -> Self {
+        Self::nyaa()
+    }
+}
+
+impl Site {
+    pub fn new(name: impl Into<String>, urls: Vec<String>, kind: SiteKind) -> Self {
+        Self {
+            name: name.into(),
+            urls,
+            kind,
+        }
+    }
+
+    pub fn nyaa() -> Self {
+        Self::new("nyaa", vec!["https://nyaa.si".to_string()], SiteKind::Nyaa)
+    }
+
+    pub fn sukebei() -> Self {
+        Self::new(
+            "sukebei",
+            vec!["https://sukebei.nyaa.si".to_string()],
+            SiteKind::Sukebei,
+        )
+    }
+
+    pub fn categories(&self) -> &'static [Category] {
+        self.kind.categories()
+    }
+}
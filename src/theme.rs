@@ -1,3 +1,4 @@
+use crate::config;
 use ratatui::style::Color;
 use serde::Deserialize;
 use std::fs;
@@ -12,6 +13,10 @@ pub struct ThemeConfig {
     pub selection_bg: String,
     pub border: String,
     pub border_focus: String,
+    #[serde(default)]
+    pub trusted: Option<String>,
+    #[serde(default)]
+    pub remake: Option<String>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -22,6 +27,8 @@ pub struct Theme {
     pub selection_bg: Color,
     pub border: Color,
     pub border_focus: Color,
+    pub trusted: Color,
+    pub remake: Color,
 }
 
 impl Default for Theme {
@@ -33,6 +40,8 @@ impl Default for Theme {
             selection_bg: Color::DarkGray,
             border: Color::DarkGray,
             border_focus: Color::Blue,
+            trusted: Color::Green,
+            remake: Color::Red,
         }
     }
 }
@@ -62,21 +71,7 @@ impl Theme {
     }
 
     fn get_config_path() -> Option<PathBuf> {
-        std::env::current_dir()
-            .ok()
-            .map(|cwd| cwd.join("theme.json"))
-            .filter(|p| p.exists())
-            .or_else(|| {
-                std::env::current_exe()
-                    .ok()
-                    .and_then(|p| p.parent().map(|d| d.join("theme.json")))
-                    .filter(|p| p.exists())
-            })
-            .or_else(|| {
-                directories::ProjectDirs::from("com", "nyaa-rs", "nyaa")
-                    .map(|proj| proj.config_dir().join("theme.json"))
-                    .filter(|p| p.exists())
-            })
+        config::find_file("theme.json")
     }
 
     fn from_config(config: ThemeConfig) -> Self {
@@ -87,6 +82,16 @@ impl Theme {
             selection_bg: parse_color(&config.selection_bg).unwrap_or(Color::DarkGray),
             border: parse_color(&config.border).unwrap_or(Color::DarkGray),
             border_focus: parse_color(&config.border_focus).unwrap_or(Color::Blue),
+            trusted: config
+                .trusted
+                .as_deref()
+                .and_then(parse_color)
+                .unwrap_or(Color::Green),
+            remake: config
+                .remake
+                .as_deref()
+                .and_then(parse_color)
+                .unwrap_or(Color::Red),
         }
     }
 }
@@ -1,13 +1,74 @@
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Flex, Layout, Margin, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Cell, HighlightSpacing, Paragraph, Row, Table},
+    widgets::{
+        Block, BorderType, Borders, Cell, Clear, HighlightSpacing, List, ListItem, Paragraph, Row,
+        Table, Wrap,
+    },
 };
 
-use crate::app::{App, InputMode};
+use crate::app::{App, DetailsPane, InputMode, LocalSort};
+use crate::group::RowKind;
 use crate::theme::Theme;
+use chrono::{DateTime, Local, Utc};
+use nyaa_rs::model::{Order, TorrentStatus};
+use serde::Deserialize;
+
+/// how the date column is shown, `D` cycles through them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateFormat {
+    #[default]
+    Utc,
+    Local,
+    /// `3h ago`
+    Relative,
+}
+
+impl DateFormat {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Utc => Self::Local,
+            Self::Local => Self::Relative,
+            Self::Relative => Self::Utc,
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            Self::Utc => "date (utc)",
+            Self::Local => "date",
+            Self::Relative => "age",
+        }
+    }
+
+    pub fn format(self, date: DateTime<Utc>) -> String {
+        match self {
+            Self::Utc => date.format("%Y-%m-%d %H:%M").to_string(),
+            Self::Local => date
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            Self::Relative => {
+                let age = Utc::now().signed_duration_since(date);
+                match age.num_minutes() {
+                    ..1 => "just now".to_string(),
+                    m @ ..60 => format!("{m}m ago"),
+                    _ => match age.num_hours() {
+                        h @ ..24 => format!("{h}h ago"),
+                        _ => match age.num_days() {
+                            d @ ..30 => format!("{d}d ago"),
+                            d @ ..365 => format!("{}mo ago", d / 30),
+                            d => format!("{}y ago", d / 365),
+                        },
+                    },
+                }
+            }
+        }
+    }
+}
 
 pub fn ui(f: &mut Frame, app: &mut App) {
     let theme = app.theme;
@@ -28,13 +89,22 @@ pub fn ui(f: &mut Frame, app: &mut App) {
 
     render_search(f, app, chunks[0], &theme);
     render_table(f, app, chunks[1], &theme);
+    render_status(f, app, chunks[2], &theme);
     render_footer(f, app, chunks[3], &theme);
+
+    match app.search.mode {
+        InputMode::Help => render_help(f, &theme),
+        InputMode::CategoryPicker => render_category_picker(f, app, &theme),
+        InputMode::SinkPicker => render_sink_picker(f, app, &theme),
+        InputMode::Details => render_details(f, app, &theme),
+        _ => {}
+    }
 }
 
 fn render_search(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     let (border_color, text_style) = match app.search.mode {
-        InputMode::Normal => (theme.border, Style::default().fg(theme.fg)),
         InputMode::Editing => (theme.border_focus, Style::default().fg(theme.primary)),
+        _ => (theme.border, Style::default().fg(theme.fg)),
     };
 
     let input = Paragraph::new(app.search.input.as_str())
@@ -54,15 +124,62 @@ fn render_search(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     f.render_widget(input, area);
 }
 
-const DATE_WIDTH: u16 = 10;
+const MARK_WIDTH: u16 = 1;
+const DATE_WIDTH: u16 = 16;
 const SIZE_WIDTH: u16 = 10;
 const SEEDERS_WIDTH: u16 = 10;
 const DOWNLOADS_WIDTH: u16 = 8;
-const SPACERS: u16 = 5;
+const GROUP_WIDTH: u16 = 14;
+const EPISODE_WIDTH: u16 = 8;
+const RESOLUTION_WIDTH: u16 = 5;
+const CODEC_WIDTH: u16 = 6;
+const SOURCE_WIDTH: u16 = 7;
 
 fn render_table(f: &mut Frame, app: &mut App, area: Rect, theme: &Theme) {
-    let header_cells = ["date", "title", "size", "s / l", "dls"].iter().map(|h| {
-        Cell::from(*h).style(
+    // the arrow goes on whichever column the page is locally sorted by
+    let sorted = |label: &str, columns: &[LocalSort]| match app.table.local_sort {
+        Some((column, order)) if columns.contains(&column) => {
+            let arrow = match order {
+                Order::Ascending => "▲",
+                Order::Descending => "▼",
+            };
+            let label = match column {
+                LocalSort::Seeders | LocalSort::Leechers | LocalSort::Ratio => column.name(),
+                _ => label,
+            };
+            format!("{label} {arrow}")
+        }
+        _ => label.to_string(),
+    };
+    let release_columns = [
+        ("group", GROUP_WIDTH),
+        ("ep", EPISODE_WIDTH),
+        ("res", RESOLUTION_WIDTH),
+        ("codec", CODEC_WIDTH),
+        ("source", SOURCE_WIDTH),
+    ];
+    let release_columns = if app.release_columns {
+        &release_columns[..]
+    } else {
+        &[]
+    };
+
+    let mut header_cells = vec![
+        String::new(),
+        sorted(app.date_format.header(), &[LocalSort::Date]),
+    ];
+    header_cells.extend(release_columns.iter().map(|(label, _)| label.to_string()));
+    header_cells.extend([
+        sorted("title", &[LocalSort::Title]),
+        sorted("size", &[LocalSort::Size]),
+        sorted(
+            "s / l",
+            &[LocalSort::Seeders, LocalSort::Leechers, LocalSort::Ratio],
+        ),
+        sorted("dls", &[LocalSort::Downloads]),
+    ]);
+    let header_cells = header_cells.into_iter().map(|h| {
+        Cell::from(h).style(
             Style::default()
                 .fg(theme.primary)
                 .add_modifier(Modifier::BOLD),
@@ -71,75 +188,170 @@ fn render_table(f: &mut Frame, app: &mut App, area: Rect, theme: &Theme) {
 
     let header = Row::new(header_cells).height(1).bottom_margin(1);
 
-    let fixed_width = DATE_WIDTH + SIZE_WIDTH + SEEDERS_WIDTH + DOWNLOADS_WIDTH;
+    let mut widths = vec![
+        Constraint::Length(MARK_WIDTH),
+        Constraint::Length(DATE_WIDTH),
+    ];
+    widths.extend(release_columns.iter().map(|&(_, w)| Constraint::Length(w)));
+    widths.extend([
+        Constraint::Min(50),
+        Constraint::Length(SIZE_WIDTH),
+        Constraint::Length(SEEDERS_WIDTH),
+        Constraint::Length(DOWNLOADS_WIDTH),
+    ]);
+
+    // lay the header out the same way the table does so clicks can find it
+    let inner = area.inner(Margin::new(1, 1));
+    let [_, columns] = Layout::horizontal([Constraint::Length(1), Constraint::Fill(0)])
+        .areas(Rect { height: 1, ..inner });
+    let cells = Layout::horizontal(widths.iter().copied())
+        .flex(Flex::Start)
+        .spacing(1)
+        .split(columns);
+    let title_column = 2 + release_columns.len();
+    app.table.column_areas = vec![
+        (cells[1], LocalSort::Date),
+        (cells[title_column], LocalSort::Title),
+        (cells[title_column + 1], LocalSort::Size),
+        (cells[title_column + 2], LocalSort::Seeders),
+        (cells[title_column + 3], LocalSort::Downloads),
+    ];
+
+    let fixed_width = MARK_WIDTH
+        + DATE_WIDTH
+        + SIZE_WIDTH
+        + SEEDERS_WIDTH
+        + DOWNLOADS_WIDTH
+        + release_columns.iter().map(|(_, w)| w).sum::<u16>();
+    // one space between each column and one before the first
+    let spacers = widths.len() as u16;
     let title_width = area
         .width
         .saturating_sub(2)
         .saturating_sub(fixed_width)
-        .saturating_sub(SPACERS) as usize;
+        .saturating_sub(spacers) as usize;
     let title_width = title_width.max(10);
 
     let selected_idx = app.table.state.selected();
     let rows = app.table.results.iter().enumerate().map(|(i, item)| {
         let is_selected = selected_idx == Some(i);
-        let title_content = marquee(&item.title, title_width, app.animation_tick, is_selected);
+        let prefix = match app.table.kinds.get(i) {
+            Some(RowKind::Group {
+                others, expanded, ..
+            }) => format!("{} +{others} ", if *expanded { "▾" } else { "▸" }),
+            Some(RowKind::Member { .. }) => "  └ ".to_string(),
+            _ => String::new(),
+        };
+        let width = title_width.saturating_sub(prefix.chars().count()).max(1);
+        let title_chars = marquee(&item.title, width, app.animation_tick, is_selected);
+        let matches = app.table.matches.get(i).map_or(&[][..], Vec::as_slice);
+        let mut title_content = highlight(&title_chars, matches, theme);
+        if !prefix.is_empty() {
+            title_content.spans.insert(
+                0,
+                Span::styled(prefix, Style::default().fg(theme.secondary)),
+            );
+        }
 
-        let cells = vec![
-            Cell::from(item.date.as_str()),
+        let mark = if app.table.is_marked(item) { "●" } else { "" };
+        let mut cells = vec![
+            Cell::from(mark).style(Style::default().fg(theme.primary)),
+            Cell::from(app.date_format.format(item.date)),
+        ];
+        if let Some(release) = app.table.releases.get(i).filter(|_| app.release_columns) {
+            let text = |field: &Option<String>| field.clone().unwrap_or_default();
+            cells.extend([
+                Cell::from(text(&release.group)),
+                Cell::from(release.episode_label()),
+                Cell::from(
+                    release
+                        .resolution
+                        .map(|r| format!("{r}p"))
+                        .unwrap_or_default(),
+                ),
+                Cell::from(text(&release.video_codec)),
+                Cell::from(text(&release.source)),
+            ]);
+        }
+        cells.extend([
             Cell::from(title_content).style(Style::default().add_modifier(Modifier::BOLD)),
             Cell::from(item.size.as_str()),
             Cell::from(format!("{} / {}", item.seeders, item.leechers)),
             Cell::from(item.downloads.to_string()),
-        ];
-        Row::new(cells)
-            .height(1)
-            .style(Style::default().fg(theme.fg))
+        ]);
+        let fg = match item.status {
+            TorrentStatus::Normal => theme.fg,
+            TorrentStatus::Trusted => theme.trusted,
+            TorrentStatus::Remake => theme.remake,
+        };
+        Row::new(cells).height(1).style(Style::default().fg(fg))
     });
 
-    let title = format!(
-        " results (sort: {}) (page {}) ",
-        app.table.current_sort, app.table.current_page
+    let mut title = format!(
+        " results ({} / {}) (sort: {} {}) (filter: {}) (page {}) ",
+        app.site().name,
+        app.table.current_category.label(),
+        app.table.current_sort,
+        app.table.current_order,
+        app.table.current_filter.name(),
+        app.table.current_page
     );
+    if let Some((column, order)) = app.table.local_sort {
+        title.push_str(&format!("(local: {} {}) ", column.name(), order));
+    }
+    let page_filter = &app.table.page_filter;
+    if page_filter.is_active() || app.search.mode == InputMode::Filter {
+        let kind = if page_filter.regex {
+            "regex"
+        } else {
+            "matching"
+        };
+        let invalid = if page_filter.is_valid() {
+            ""
+        } else {
+            ", invalid"
+        };
+        title.push_str(&format!("({kind} \"{}\"{invalid}) ", page_filter.text));
+    }
+    if app.table.grouped {
+        title.push_str("(grouped) ");
+    }
+    if !app.table.marked.is_empty() {
+        title.push_str(&format!("({} marked) ", app.table.marked.len()));
+    }
 
-    let t = Table::new(
-        rows,
-        [
-            Constraint::Length(DATE_WIDTH),
-            Constraint::Min(50),
-            Constraint::Length(SIZE_WIDTH),
-            Constraint::Length(SEEDERS_WIDTH),
-            Constraint::Length(DOWNLOADS_WIDTH),
-        ],
-    )
-    .header(header)
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(theme.border))
-            .title(title)
-            .title_style(Style::default().fg(theme.secondary)),
-    )
-    .row_highlight_style(
-        Style::default()
-            .bg(theme.selection_bg)
-            .add_modifier(Modifier::BOLD),
-    )
-    .highlight_symbol(" ")
-    .highlight_spacing(HighlightSpacing::Always);
+    let t = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(theme.border))
+                .title(title)
+                .title_style(Style::default().fg(theme.secondary)),
+        )
+        .row_highlight_style(
+            Style::default()
+                .bg(theme.selection_bg)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(" ")
+        .highlight_spacing(HighlightSpacing::Always);
 
     f.render_stateful_widget(t, area, &mut app.table.state);
 }
 
-fn marquee(text: &str, width: usize, tick: usize, is_selected: bool) -> String {
+/// the visible chars of a title with their index into it, scrolling it when
+/// it is selected and too long; the gap between loops is indexed past the end
+fn marquee(text: &str, width: usize, tick: usize, is_selected: bool) -> Vec<(usize, char)> {
     let char_count = text.chars().count();
     if char_count <= width || !is_selected {
-        return text.to_string();
+        return text.chars().enumerate().collect();
     }
 
     const DELAY_TICKS: usize = 5;
     if tick <= DELAY_TICKS {
-        return text.to_string();
+        return text.chars().enumerate().collect();
     }
 
     const SEPARATOR: &str = "   ";
@@ -148,12 +360,304 @@ fn marquee(text: &str, width: usize, tick: usize, is_selected: bool) -> String {
 
     text.chars()
         .chain(SEPARATOR.chars())
+        .enumerate()
         .cycle()
         .skip(start)
         .take(width)
         .collect()
 }
 
+/// splits the title into spans so the chars the `/` filter matched stand out
+fn highlight(chars: &[(usize, char)], matches: &[usize], theme: &Theme) -> Line<'static> {
+    let matched = Style::default()
+        .fg(theme.primary)
+        .add_modifier(Modifier::UNDERLINED);
+    let is_match = |i: &usize| matches.contains(i);
+
+    chars
+        .chunk_by(|(a, _), (b, _)| is_match(a) == is_match(b))
+        .map(|chunk| {
+            let text: String = chunk.iter().map(|(_, c)| c).collect();
+            if is_match(&chunk[0].0) {
+                Span::styled(text, matched)
+            } else {
+                Span::raw(text)
+            }
+        })
+        .collect()
+}
+
+fn render_category_picker(f: &mut Frame, app: &mut App, theme: &Theme) {
+    let categories = app.site().categories();
+    let items = categories.iter().map(|c| {
+        let (text, style) = if c.is_subcategory() {
+            (format!("  {}", c.name()), Style::default().fg(theme.fg))
+        } else {
+            (
+                c.name().to_string(),
+                Style::default()
+                    .fg(theme.secondary)
+                    .add_modifier(Modifier::BOLD),
+            )
+        };
+        let marker = if *c == app.table.current_category {
+            " *"
+        } else {
+            ""
+        };
+        ListItem::new(format!("{text}{marker}")).style(style)
+    });
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(theme.border_focus))
+                .title(" category ")
+                .title_style(
+                    Style::default()
+                        .fg(theme.primary)
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection_bg)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(" ")
+        .highlight_spacing(HighlightSpacing::Always);
+
+    let area = centered_rect(40, categories.len() as u16 + 2, f.area());
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut app.category_state);
+}
+
+fn render_sink_picker(f: &mut Frame, app: &mut App, theme: &Theme) {
+    let items = app.sinks.iter().enumerate().map(|(i, sink)| {
+        let label = if i == 0 {
+            format!("{} (default)", sink.name())
+        } else {
+            sink.name().to_string()
+        };
+        ListItem::new(label).style(Style::default().fg(theme.fg))
+    });
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(theme.border_focus))
+                .title(" send to ")
+                .title_style(
+                    Style::default()
+                        .fg(theme.primary)
+                        .add_modifier(Modifier::BOLD),
+                ),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection_bg)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(" ")
+        .highlight_spacing(HighlightSpacing::Always);
+
+    let area = centered_rect(32, app.sinks.len() as u16 + 2, f.area());
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut app.sink_state);
+}
+
+fn render_details(f: &mut Frame, app: &mut App, theme: &Theme) {
+    let Some(state) = &mut app.details else {
+        return;
+    };
+
+    let area = centered_rect(
+        f.area().width.saturating_mul(4) / 5,
+        f.area().height.saturating_mul(4) / 5,
+        f.area(),
+    );
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.border_focus))
+        .title(format!(" {} ", state.torrent.title))
+        .title_style(
+            Style::default()
+                .fg(theme.primary)
+                .add_modifier(Modifier::BOLD),
+        );
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let Some(details) = &state.details else {
+        let p = Paragraph::new("loading...")
+            .style(Style::default().fg(theme.primary))
+            .alignment(ratatui::layout::Alignment::Center);
+        f.render_widget(p, inner);
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(5),
+            Constraint::Min(3),
+            Constraint::Percentage(40),
+        ])
+        .split(inner);
+
+    let label_style = Style::default()
+        .fg(theme.secondary)
+        .add_modifier(Modifier::BOLD);
+    let value_style = Style::default().fg(theme.fg);
+    let field = |label: &'static str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{label:<12}"), label_style),
+            Span::styled(value, value_style),
+        ])
+    };
+    let info = Paragraph::new(vec![
+        field("submitter", details.submitter.clone()),
+        field("date", details.timestamp.clone()),
+        field(
+            "information",
+            details
+                .information
+                .clone()
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+        field("info hash", details.info_hash.clone()),
+        field("size", state.torrent.size.clone()),
+    ]);
+    f.render_widget(info, chunks[0]);
+
+    let description = Paragraph::new(details.description.as_str())
+        .style(value_style)
+        .wrap(Wrap { trim: false })
+        .scroll((state.scroll, 0))
+        .block(
+            Block::default()
+                .borders(Borders::TOP)
+                .border_style(Style::default().fg(theme.border))
+                .title(" description ")
+                .title_style(Style::default().fg(theme.secondary)),
+        );
+    f.render_widget(description, chunks[1]);
+
+    if state.pane == DetailsPane::Comments {
+        let lines: Vec<Line> = details
+            .comments
+            .iter()
+            .flat_map(|comment| {
+                let mut header = vec![Span::styled(comment.author.clone(), label_style)];
+                if comment.trusted {
+                    header.push(Span::styled(
+                        " [trusted]",
+                        Style::default().fg(theme.trusted),
+                    ));
+                }
+                if comment.uploader {
+                    header.push(Span::styled(
+                        " [uploader]",
+                        Style::default().fg(theme.primary),
+                    ));
+                }
+                header.push(Span::styled(
+                    format!(" {}", comment.timestamp),
+                    Style::default().fg(theme.border),
+                ));
+
+                std::iter::once(Line::from(header))
+                    .chain(
+                        comment
+                            .body
+                            .lines()
+                            .map(|l| Line::styled(format!("  {l}"), value_style)),
+                    )
+                    .chain(std::iter::once(Line::default()))
+            })
+            .collect();
+
+        let comments = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((state.comment_scroll, 0))
+            .block(
+                Block::default()
+                    .borders(Borders::TOP)
+                    .border_style(Style::default().fg(theme.border_focus))
+                    .title(format!(" comments ({}) ", details.comments.len()))
+                    .title_style(Style::default().fg(theme.secondary)),
+            );
+        f.render_widget(comments, chunks[2]);
+        return;
+    }
+
+    let items: Vec<ListItem> = state
+        .visible_files()
+        .iter()
+        .map(|file| {
+            let indent = "  ".repeat(file.depth);
+            let line = if file.entry.is_folder() {
+                let icon = if state.collapsed.contains(&file.path) {
+                    "▸"
+                } else {
+                    "▾"
+                };
+                Line::from(Span::styled(
+                    format!("{indent}{icon} {}/", file.entry.name),
+                    label_style,
+                ))
+            } else {
+                Line::from(vec![
+                    Span::styled(format!("{indent}  {}", file.entry.name), value_style),
+                    Span::styled(
+                        format!(" ({})", file.entry.size.as_deref().unwrap_or_default()),
+                        Style::default().fg(theme.border),
+                    ),
+                ])
+            };
+            ListItem::new(line)
+        })
+        .collect();
+
+    let files = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::TOP)
+                .border_style(Style::default().fg(theme.border_focus))
+                .title(format!(
+                    " files (comments: {}) ",
+                    state.torrent.comments.max(details.comments.len() as u32)
+                ))
+                .title_style(Style::default().fg(theme.secondary)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection_bg)
+                .add_modifier(Modifier::BOLD),
+        );
+    f.render_stateful_widget(files, chunks[2], &mut state.files);
+}
+
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
 fn render_footer(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     let base_style = Style::default().fg(theme.border);
     let key_style = Style::default()
@@ -163,39 +667,243 @@ fn render_footer(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
     let k = |s: &'static str| Span::styled(s, key_style);
     let t = |s: &'static str| Span::styled(s, base_style);
 
-    let mut spans = match app.search.mode {
+    let spans = match app.search.mode {
         InputMode::Normal => vec![
             k("q"),
             t(" quit, "),
             k("tab"),
             t(" search, "),
+            k("enter"),
+            t(" send, "),
+            k("/"),
+            t(" filter page, "),
+            k("?"),
+            t(" all keys "),
+        ],
+        InputMode::Help => vec![k("esc/?"), t(" close ")],
+        InputMode::Editing => vec![k("tab/esc"), t(" list, "), k("enter"), t(" submit ")],
+        InputMode::Filter => vec![
+            t("type to filter, "),
+            k("group:x res:1080 ep:12"),
+            t(" fields, "),
+            k("tab"),
+            t(" fuzzy/regex, "),
+            k("↑/↓"),
+            t(" nav, "),
+            k("enter"),
+            t(" keep, "),
+            k("esc"),
+            t(" clear "),
+        ],
+        InputMode::LocalSort => vec![
+            t("sort page by "),
+            k("s"),
+            t(" seeders, "),
+            k("l"),
+            t(" leechers, "),
+            k("r"),
+            t(" ratio, "),
+            k("b"),
+            t(" size, "),
+            k("d"),
+            t(" date, "),
+            k("t"),
+            t(" title, "),
+            k("c"),
+            t(" downloads, "),
+            k("x"),
+            t(" off "),
+        ],
+        InputMode::Copy => vec![
+            t("copy "),
+            k("m"),
+            t(" magnet, "),
+            k("l"),
+            t(" link, "),
+            k("h"),
+            t(" info hash, "),
+            k("t"),
+            t(" title, "),
+            k("esc"),
+            t(" cancel "),
+        ],
+        InputMode::Details => vec![
+            k("w/s/↑/↓"),
+            t(" nav, "),
+            k("tab/m"),
+            t(" files/comments, "),
+            k("enter/space"),
+            t(" fold, "),
+            k("J/K"),
+            t(" scroll, "),
+            k("esc"),
+            t(" close "),
+        ],
+        InputMode::CategoryPicker | InputMode::SinkPicker => vec![
             k("w/s/↑/↓"),
             t(" nav, "),
             k("enter"),
-            t(" open, "),
-            k("z"),
-            t(" sort, "),
-            k("a/d/←/→"),
-            t(" page "),
+            t(" select, "),
+            k("esc"),
+            t(" close "),
         ],
-        InputMode::Editing => vec![k("tab/esc"), t(" list, "), k("enter"), t(" submit ")],
     };
 
-    if app.search.is_loading {
-        spans.push(Span::styled(
-            " [loading...]",
-            Style::default().fg(theme.primary),
-        ));
-    } else if let Some(msg) = app.search.messages.last() {
-        spans.push(Span::styled(
-            format!(" [{}]", msg),
-            Style::default().fg(theme.primary),
-        ));
-    }
-
     let p = Paragraph::new(Line::from(spans))
         .block(Block::default())
         .alignment(ratatui::layout::Alignment::Center);
 
     f.render_widget(p, area);
 }
+
+/// the search spinner or the latest message, on a line of its own so the
+/// key hints never push it off screen
+fn render_status(f: &mut Frame, app: &App, area: Rect, theme: &Theme) {
+    let style = Style::default().fg(theme.primary);
+    let line = if app.search.is_loading {
+        let elapsed = app
+            .search
+            .loading_since
+            .map(|t| t.elapsed())
+            .unwrap_or_default();
+        let frame = SPINNER[(elapsed.as_millis() / 200) as usize % SPINNER.len()];
+        Line::from(vec![
+            Span::styled(format!("{frame} searching {}s, ", elapsed.as_secs()), style),
+            Span::styled(
+                "esc",
+                Style::default()
+                    .fg(theme.secondary)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" cancels", style),
+        ])
+    } else if let Some(msg) = app.search.messages.last() {
+        Line::styled(msg.as_str(), style)
+    } else {
+        return;
+    };
+
+    let p = Paragraph::new(line).alignment(ratatui::layout::Alignment::Center);
+    f.render_widget(p, area);
+}
+
+/// every key of the results list, shown by `?` in two columns
+const HELP: [(&str, &str); 24] = [
+    ("q", "quit"),
+    ("tab / i", "edit the search"),
+    ("w/s/↑/↓", "move"),
+    ("a/d/←/→", "previous / next page"),
+    ("enter", "send"),
+    ("p", "send to another sink"),
+    ("t", "save the .torrent"),
+    ("y", "copy link, hash, title"),
+    ("space", "mark and move down"),
+    ("A / I / u", "mark all / invert / none"),
+    ("z / 1-7", "sort on the site"),
+    ("x", "flip the order"),
+    ("o", "sort the loaded page"),
+    ("/", "filter the loaded page"),
+    ("esc", "clear filter, cancel"),
+    ("f", "trusted / no remakes"),
+    ("c", "category"),
+    ("S", "site"),
+    ("v", "details and files"),
+    ("m", "comments"),
+    ("D", "date format"),
+    ("R", "release columns"),
+    ("G", "group releases"),
+    ("e", "expand a group"),
+];
+
+fn render_help(f: &mut Frame, theme: &Theme) {
+    let key_width = HELP
+        .iter()
+        .map(|(k, _)| k.chars().count())
+        .max()
+        .unwrap_or(0);
+    let action_width = HELP
+        .iter()
+        .map(|(_, a)| a.chars().count())
+        .max()
+        .unwrap_or(0);
+    let key_style = Style::default()
+        .fg(theme.secondary)
+        .add_modifier(Modifier::BOLD);
+    let action_style = Style::default().fg(theme.fg);
+
+    let rows = HELP.len().div_ceil(2);
+    let lines: Vec<Line> = (0..rows)
+        .map(|row| {
+            [HELP.get(row), HELP.get(row + rows)]
+                .into_iter()
+                .flatten()
+                .flat_map(|(key, action)| {
+                    [
+                        Span::styled(format!(" {key:<key_width$}  "), key_style),
+                        Span::styled(format!("{action:<action_width$} "), action_style),
+                    ]
+                })
+                .collect::<Line>()
+        })
+        .collect();
+
+    let help = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme.border_focus))
+            .title(" keys ")
+            .title_style(
+                Style::default()
+                    .fg(theme.primary)
+                    .add_modifier(Modifier::BOLD),
+            ),
+    );
+
+    let width = 2 * (key_width + action_width + 4) + 2;
+    let area = centered_rect(width as u16, rows as u16 + 2, f.area());
+    f.render_widget(Clear, area);
+    f.render_widget(help, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn screen(app: &mut App, width: u16) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, 24)).unwrap();
+        terminal.draw(|f| ui(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>() + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn status_messages_get_their_own_line() {
+        let mut app = App::new();
+        app.search.mode = InputMode::Normal;
+        app.search
+            .messages
+            .push("qbittorrent: qbittorrent refused the request, check credentials".to_string());
+
+        let screen = screen(&mut app, 80);
+        assert!(screen.contains("check credentials"), "{screen}");
+        assert!(screen.contains("? all keys"), "{screen}");
+    }
+
+    #[test]
+    fn help_lists_every_key() {
+        let mut app = App::new();
+        app.search.mode = InputMode::Help;
+
+        let screen = screen(&mut app, 80);
+        for (key, action) in HELP {
+            assert!(screen.contains(action), "{key} missing from\n{screen}");
+        }
+    }
+}